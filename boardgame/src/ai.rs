@@ -0,0 +1,146 @@
+use crate::config::{AiConfig, EngineConfig};
+use crate::mode::Mode;
+use crate::move_system::PendingMoves;
+use crate::turn::{Controller, Controllers, Outcome};
+use amethyst::ecs::{Read, ReadExpect, System, Write};
+use chess::{search, BitPosition, Clock, Game, GameStatus, Limits, Move, Position, UciEngine};
+use log::{error, info};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// A game to find a move in and where to send the move to.
+type EngineRequest = (Game, Sender<Option<Move>>);
+
+/// Plays for the players controlled by `Controller::Ai` or `Controller::Uci`.
+///
+/// The search runs on its own thread so the frame loop keeps going, the
+/// move it finds is handed to the `MoveSystem` through `PendingMoves`.
+#[derive(Default)]
+pub struct AiSystem {
+    /// Position being searched and where the result will arrive.
+    thinking: Option<(Position, Receiver<Option<Move>>)>,
+    /// The thread talking to the external engine, started on its first move.
+    engine: Option<Sender<EngineRequest>>,
+}
+
+impl<'s> System<'s> for AiSystem {
+    type SystemData = (
+        ReadExpect<'s, Game>,
+        Read<'s, Controllers>,
+        Read<'s, AiConfig>,
+        Read<'s, EngineConfig>,
+        Read<'s, Mode>,
+        Write<'s, PendingMoves>,
+        Option<Read<'s, Clock>>,
+        Read<'s, Outcome>,
+    );
+
+    fn run(
+        &mut self,
+        (game, controllers, ai_config, engine_config, mode, mut pending, clock, outcome): Self::SystemData,
+    ) {
+        if let Some((searched, receiver)) = &self.thinking {
+            match receiver.try_recv() {
+                Ok(best_move) => {
+                    // the game may have been taken back while searching
+                    if searched == game.position() {
+                        if let Some(mv) = best_move {
+                            pending.moves.push(mv);
+                        }
+                    }
+                    self.thinking = None;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.thinking = None,
+            }
+            return;
+        }
+
+        let position = game.position();
+        if *mode != Mode::Play
+            || !pending.moves.is_empty()
+            || outcome.status != GameStatus::Ongoing
+            || clock.map_or(false, |clock| clock.flagged().is_some())
+        {
+            return;
+        }
+        let receiver = match controllers.get(position.side_to_move()) {
+            Controller::Human | Controller::Remote => return,
+            Controller::Ai => self.search(position.clone(), &ai_config),
+            Controller::Uci => match self.ask_engine(&game, &engine_config) {
+                Some(receiver) => receiver,
+                None => return,
+            },
+        };
+        self.thinking = Some((position.clone(), receiver));
+    }
+}
+
+impl AiSystem {
+    /// Starts the built-in search on its own thread.
+    fn search(&self, position: Position, ai_config: &AiConfig) -> Receiver<Option<Move>> {
+        let limits = Limits {
+            depth: ai_config.depth,
+            time: Some(Duration::from_millis(ai_config.time_ms)),
+        };
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            // the receiver is gone if the game was closed meanwhile
+            let result = match BitPosition::from_position(&position) {
+                Some(bits) => search(&bits, &limits),
+                None => search(&position, &limits),
+            };
+            let _ = sender.send(result.best_move);
+        });
+        receiver
+    }
+
+    /// Hands the game to the engine thread, starting it first if needed.
+    /// Returns `None` once the engine failed, so it is not restarted every frame.
+    fn ask_engine(&mut self, game: &Game, config: &EngineConfig) -> Option<Receiver<Option<Move>>> {
+        if self.engine.is_none() {
+            self.engine = Some(spawn_engine(config.clone()));
+        }
+        let (sender, receiver) = channel();
+        let engine = self.engine.as_ref()?;
+        if engine.send((game.clone(), sender)).is_err() {
+            // the engine thread ended; the error was logged there. Keep the
+            // dead sender around so the next frame does not start it again.
+            return None;
+        }
+        Some(receiver)
+    }
+}
+
+/// Starts the configured engine on a thread that answers requests until the
+/// engine fails or the game is closed.
+fn spawn_engine(config: EngineConfig) -> Sender<EngineRequest> {
+    let (sender, requests) = channel::<EngineRequest>();
+    thread::spawn(move || {
+        let mut engine = match UciEngine::spawn(&config.command, &config.args) {
+            Ok(engine) => engine,
+            Err(err) => {
+                error!("failed to start engine {}: {}", config.command, err);
+                return;
+            }
+        };
+        info!(
+            "playing against {}",
+            engine.name.as_deref().unwrap_or(&config.command)
+        );
+        let go = format!("go movetime {}", config.movetime_ms);
+        for (game, reply) in requests {
+            match engine.best_move(&game, &go) {
+                Ok(best_move) => {
+                    let _ = reply.send(best_move);
+                }
+                Err(err) => {
+                    error!("engine {} failed: {}", config.command, err);
+                    return;
+                }
+            }
+        }
+    });
+    sender
+}
@@ -0,0 +1,77 @@
+use crate::piece::{sprite_index, Piece};
+use amethyst::core::{Parent, Transform};
+use amethyst::ecs::{Component, DenseVecStorage, Entities, Entity, Join, ReadStorage, System, WriteStorage};
+use amethyst::renderer::SpriteRender;
+use chess::PieceType;
+use nalgebra::base::Vector3;
+use std::collections::HashSet;
+
+/// Where the badge sits on its piece, in pixels of the piece sprite from its center.
+const OFFSET: f32 = 18.;
+const SCALE: f32 = 0.45;
+
+/// A small knight drawn onto a piece, see `BadgeSystem`.
+pub struct Badge {
+    pub piece: Entity,
+}
+impl Component for Badge {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Returns true for the pieces that jump like a knight on top of how they
+/// look: the archbishop, drawn as a bishop, and the chancellor, drawn as a rook.
+pub fn has_badge(piece_type: PieceType) -> bool {
+    matches!(piece_type, PieceType::ARCHBISHOP | PieceType::CHANCELLOR)
+}
+
+/// Puts a badge on every piece `has_badge` is true for, and removes those
+/// whose piece was captured or is no longer such a piece. The badge is a
+/// child of the piece, so it slides, is dragged and turns with it.
+pub struct BadgeSystem;
+
+impl<'s> System<'s> for BadgeSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Piece>,
+        WriteStorage<'s, Badge>,
+        WriteStorage<'s, Parent>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, pieces, mut badges, mut parents, mut transforms, mut sprites): Self::SystemData,
+    ) {
+        let mut badged = HashSet::new();
+        for (entity, badge) in (&entities, &badges).join() {
+            match pieces.get(badge.piece) {
+                Some(piece) if has_badge(piece.piece_type) => {
+                    badged.insert(badge.piece);
+                }
+                _ => entities.delete(entity).expect("failed to delete badge"),
+            }
+        }
+
+        let missing: Vec<_> = (&entities, &pieces, &sprites)
+            .join()
+            .filter(|(entity, piece, _)| has_badge(piece.piece_type) && !badged.contains(entity))
+            .map(|(entity, piece, sprite)| {
+                let knight = chess::Piece::new(piece.player, PieceType::KNIGHT);
+                (entity, SpriteRender::new(sprite.sprite_sheet.clone(), sprite_index(knight)))
+            })
+            .collect();
+        for (piece, sprite) in missing {
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(OFFSET, -OFFSET, 0.1);
+            transform.set_scale(Vector3::new(SCALE, SCALE, 1.));
+            entities
+                .build_entity()
+                .with(Badge { piece }, &mut badges)
+                .with(Parent { entity: piece }, &mut parents)
+                .with(transform, &mut transforms)
+                .with(sprite, &mut sprites)
+                .build();
+        }
+    }
+}
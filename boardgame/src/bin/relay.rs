@@ -0,0 +1,30 @@
+//! Hosts a game between two players and shows it to any number of
+//! spectators, without a window. Players use "Join network game" on the
+//! first address, spectators "Watch network game" on the second, e.g.
+//! `cargo run --bin relay -- 0.0.0.0:7878 0.0.0.0:7879`.
+use chess::{serve_relay, Position};
+use std::env;
+use std::io;
+use std::net::TcpListener;
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    let players = args.next().unwrap_or_else(|| "0.0.0.0:7878".to_string());
+    let spectators = args.next().unwrap_or_else(|| "0.0.0.0:7879".to_string());
+    let start = match args.next() {
+        Some(fen) => Position::from_fen(&fen)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
+        None => Position::standard(),
+    };
+
+    let players = TcpListener::bind(&players)?;
+    let spectators = TcpListener::bind(&spectators)?;
+    println!(
+        "players on {}, spectators on {}",
+        players.local_addr()?,
+        spectators.local_addr()?
+    );
+    let (game, status) = serve_relay(&players, spectators, start)?;
+    println!("game over after {} plies: {}", game.ply(), status.result());
+    Ok(())
+}
@@ -0,0 +1,9 @@
+//! The chess engine without a window, speaking UCI on stdin and stdout so it
+//! can be used from chess GUIs and tournament managers, e.g.
+//! `cutechess-cli -engine cmd=target/release/uci -engine cmd=stockfish ...`.
+use std::io;
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    chess::serve_uci(stdin.lock(), io::stdout())
+}
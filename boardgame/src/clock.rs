@@ -0,0 +1,136 @@
+use crate::mode::Mode;
+use crate::turn::Outcome;
+use amethyst::core::Time;
+use amethyst::ecs::{Entity, Read, ReadExpect, System, Write, WriteStorage};
+use amethyst::ui::UiText;
+use chess::{Clock, Game, GameStatus, Increment, Player, TimeControl};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Time given back per move in seconds, see `chess::Increment`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum IncrementConfig {
+    Fischer(u64),
+    Bronstein(u64),
+    Delay(u64),
+}
+
+/// A time control as written in `config/game.ron`, e.g.
+/// `(minutes: 5, increment: Fischer(3))` for 5+3.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TimeControlConfig {
+    pub minutes: u64,
+    #[serde(default)]
+    pub seconds: u64,
+    pub increment: IncrementConfig,
+}
+impl TimeControlConfig {
+    pub fn time_control(&self) -> TimeControl {
+        let increment = match self.increment {
+            IncrementConfig::Fischer(s) => Increment::Fischer(Duration::from_secs(s)),
+            IncrementConfig::Bronstein(s) => Increment::Bronstein(Duration::from_secs(s)),
+            IncrementConfig::Delay(s) => Increment::Delay(Duration::from_secs(s)),
+        };
+        TimeControl {
+            initial: Duration::from_secs(self.minutes * 60 + self.seconds),
+            increment,
+        }
+    }
+}
+
+/// The time controls to choose from and the chosen one.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    /// Name of the time control to play with, no clocks if `None`.
+    pub selected: Option<String>,
+    pub time_controls: BTreeMap<String, TimeControlConfig>,
+}
+impl ClockConfig {
+    /// The selected time control, `None` if none is selected or the name is unknown.
+    pub fn time_control(&self) -> Option<TimeControl> {
+        let name = self.selected.as_ref()?;
+        self.time_controls.get(name).map(TimeControlConfig::time_control)
+    }
+}
+
+/// The ui entities showing the remaining time of either player.
+pub struct ClockText {
+    pub first: Entity,
+    pub second: Entity,
+}
+
+/// Set by `MyState` when it redoes moves with `Game::forward`, so the
+/// `ClockSystem` does not take them for new moves.
+#[derive(Default)]
+pub struct Redone(pub bool);
+
+/// Runs the clock of the side to move and presses it after every new move.
+/// Taking moves back and redoing them only hands the turn over, without an
+/// increment. Only there when a time control is selected.
+#[derive(Default)]
+pub struct ClockSystem {
+    /// Ply of the game in the last frame, to notice moves.
+    ply: Option<usize>,
+}
+
+impl<'s> System<'s> for ClockSystem {
+    type SystemData = (
+        Option<Write<'s, Clock>>,
+        Option<ReadExpect<'s, ClockText>>,
+        ReadExpect<'s, Game>,
+        Read<'s, Mode>,
+        Read<'s, Time>,
+        Write<'s, Redone>,
+        Read<'s, Outcome>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (clock, clock_text, game, mode, time, mut redone, outcome, mut ui_texts): Self::SystemData,
+    ) {
+        let redone = std::mem::take(&mut redone.0);
+        let (mut clock, clock_text) = match (clock, clock_text) {
+            (Some(clock), Some(clock_text)) => (clock, clock_text),
+            _ => return,
+        };
+        let ply = game.ply();
+        if *mode == Mode::Play {
+            let side = game.position().side_to_move();
+            match self.ply {
+                Some(last) if ply < last || (ply > last && redone) => clock.switch(),
+                Some(last) => {
+                    for i in last..ply {
+                        // the player who moved, counting back from the side to move
+                        let mover = if (ply - i) % 2 == 1 { side.opponent() } else { side };
+                        clock.press(mover);
+                    }
+                }
+                // the moves played before, e.g. in a continued game
+                None => {}
+            }
+            if outcome.status == GameStatus::Ongoing {
+                clock.tick(side, time.delta_time());
+            }
+        }
+        self.ply = Some(ply);
+
+        for &(player, text) in &[(Player::FIRST, clock_text.first), (Player::SECOND, clock_text.second)] {
+            if let Some(ui_text) = ui_texts.get_mut(text) {
+                ui_text.text = format_time(clock.remaining(player));
+            }
+        }
+    }
+}
+
+/// `m:ss`, with tenths in the last ten seconds.
+fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs < 10 {
+        format!("0:{:02}.{}", secs, time.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
@@ -1,26 +1,119 @@
-use crate::piece;
-use crate::piece::Piece;
-use crate::piece::Status;
+use crate::grid::Grid;
+use crate::piece::{Piece, Status};
+use amethyst::assets::Handle;
 use amethyst::core::Transform;
-use amethyst::ecs::{Join, Read, ReadExpect, System, WriteStorage};
-use amethyst::input::{InputHandler, StringBindings};
-use amethyst::renderer::{palette::Srgba, resources::Tint};
-use amethyst::window::ScreenDimensions;
+use amethyst::ecs::{
+    Component, Entities, Join, NullStorage, ReadExpect, ReadStorage, System, WriteStorage,
+};
+use amethyst::renderer::{
+    palette::Srgba, resources::Tint, transparent::Transparent, SpriteRender, SpriteSheet,
+};
+use chess::{Game, Move};
+use nalgebra::base::Vector3;
 
-pub struct ColorPieceSystem;
+/// Width of a square in `whiteblack.png`.
+const SPRITE_SIZE: f32 = 20.;
+
+/// Marks the overlay entities the `ColorPieceSystem` creates, so it can
+/// remove them again.
+#[derive(Default)]
+pub struct Marker;
+impl Component for Marker {
+    type Storage = NullStorage<Self>;
+}
+
+/// Sprite sheet the markers are drawn from, the board squares of `whiteblack.png`.
+pub struct MarkerSprites {
+    pub sheet: Handle<SpriteSheet>,
+}
+
+/// Tints the selected piece and marks where it can go: a small dot on
+/// empty squares, the whole square on captures. The squares of the last
+/// move are marked as well.
+#[derive(Default)]
+pub struct ColorPieceSystem {
+    /// Selected square, ply and last move the markers were made for.
+    shown: Option<(Option<(u32, u32)>, usize, Option<Move>)>,
+}
 
 impl<'s> System<'s> for ColorPieceSystem {
-    type SystemData = (WriteStorage<'s, Piece>, WriteStorage<'s, Tint>);
-
-    fn run(&mut self, (mut pieces, mut tints): Self::SystemData) {
-        for (piece, tint) in (&mut pieces, &mut tints).join() {
-            match piece.status {
-                Status::None => {}
-                Status::Selected => {
-                    let mut tin = Tint(Srgba::new(0.5, 1., 1., 0.5));
-                    write_storage.insert(tin)
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Piece>,
+        ReadStorage<'s, Status>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, Marker>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        ReadExpect<'s, Game>,
+        ReadExpect<'s, Grid>,
+        ReadExpect<'s, MarkerSprites>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, pieces, status, mut tints, mut markers, mut transforms, mut sprites, mut transparent, game, grid, marker_sprites): Self::SystemData,
+    ) {
+        let selected = (&entities, &pieces, &status)
+            .join()
+            .find(|(_, _, st)| matches!(st, Status::Selected))
+            .map(|(entity, piece, _)| (entity, (piece.x, piece.y)));
+        let shown = (selected.map(|(_, square)| square), game.ply(), game.last_move());
+        if self.shown == Some(shown) {
+            return;
+        }
+        self.shown = Some(shown);
+
+        for (entity, _) in (&entities, &markers).join() {
+            entities.delete(entity).expect("failed to delete marker");
+        }
+        let tinted: Vec<_> = (&entities, &pieces, &tints).join().map(|(entity, _, _)| entity).collect();
+        for entity in tinted {
+            tints.remove(entity);
+        }
+
+        let mut marks = vec![];
+        if let Some(mv) = game.last_move() {
+            let last = Srgba::new(1., 0.9, 0.2, 0.5);
+            marks.push((mv.from, 1., last));
+            marks.push((mv.to, 1., last));
+        }
+        if let Some((entity, from)) = selected {
+            tints
+                .insert(entity, Tint(Srgba::new(0.5, 1., 1., 1.)))
+                .expect("failed to tint selected piece");
+            let position = game.position();
+            let mut moves = position.moves_from(from);
+            // one marker for all the pieces a pawn may promote to
+            moves.dedup_by_key(|mv| mv.to);
+            for mv in moves {
+                if position.capture_square(mv).is_some() {
+                    marks.push((mv.to, 1., Srgba::new(1., 0.2, 0.2, 0.5)));
+                } else {
+                    marks.push((mv.to, 0.3, Srgba::new(0.2, 0.8, 0.2, 0.7)));
                 }
             }
         }
+
+        let size = grid.square_size();
+        for (square, fraction, color) in marks {
+            let scale = size * fraction / SPRITE_SIZE;
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(
+                square.0 as f32 * size + size / 2.,
+                square.1 as f32 * size + size / 2.,
+                0.5,
+            );
+            transform.set_scale(Vector3::new(scale, scale, 1.));
+            entities
+                .build_entity()
+                .with(Marker, &mut markers)
+                .with(transform, &mut transforms)
+                .with(SpriteRender::new(marker_sprites.sheet.clone(), 0), &mut sprites)
+                .with(Tint(color), &mut tints)
+                .with(Transparent, &mut transparent)
+                .build();
+        }
     }
 }
@@ -0,0 +1,92 @@
+use crate::clock::ClockConfig;
+use crate::net::NetConfig;
+use crate::turn::Controllers;
+use crate::tween::AnimationConfig;
+use crate::variant::VariantConfig;
+use serde::{Deserialize, Serialize};
+
+/// Settings read from `config/game.ron`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// Position for "Load FEN" and network games in FEN, the starting
+    /// position of the variant if `None`.
+    pub fen: Option<String>,
+    /// Names written into saved games.
+    pub white: String,
+    pub black: String,
+    /// Where the `save_pgn` action writes the game to, relative to the application root.
+    pub pgn_path: String,
+    /// Where the `export_fen` action writes the current position to, relative to the application root.
+    pub fen_path: String,
+    /// Where an unfinished game is kept on closing, relative to the application root.
+    pub save_path: String,
+    /// Who moves the pieces of either side.
+    pub controllers: Controllers,
+    pub ai: AiConfig,
+    pub engine: EngineConfig,
+    pub animation: AnimationConfig,
+    pub clock: ClockConfig,
+    pub variant: VariantConfig,
+    /// Turn the board towards the side to move after every move of a human.
+    pub auto_flip: bool,
+    pub net: NetConfig,
+}
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            fen: None,
+            white: "?".to_string(),
+            black: "?".to_string(),
+            pgn_path: "game.pgn".to_string(),
+            fen_path: "position.fen".to_string(),
+            save_path: "save.ron".to_string(),
+            controllers: Controllers::default(),
+            ai: AiConfig::default(),
+            engine: EngineConfig::default(),
+            animation: AnimationConfig::default(),
+            clock: ClockConfig::default(),
+            variant: VariantConfig::default(),
+            auto_flip: false,
+            net: NetConfig::default(),
+        }
+    }
+}
+
+/// Search budget of the computer opponent.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AiConfig {
+    /// Deepest search in plies.
+    pub depth: u32,
+    /// Time per move in milliseconds.
+    pub time_ms: u64,
+}
+impl Default for AiConfig {
+    fn default() -> Self {
+        AiConfig {
+            depth: 5,
+            time_ms: 2000,
+        }
+    }
+}
+
+/// External UCI engine playing for `Controller::Uci`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    /// Program to start, looked up in `PATH` unless it is a path.
+    pub command: String,
+    pub args: Vec<String>,
+    /// Thinking time per move in milliseconds.
+    pub movetime_ms: u64,
+}
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            command: "stockfish".to_string(),
+            args: vec![],
+            movetime_ms: 1000,
+        }
+    }
+}
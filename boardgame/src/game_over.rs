@@ -0,0 +1,88 @@
+use amethyst::{
+    input::{is_close_requested, is_key_down, InputEvent, VirtualKeyCode},
+    prelude::*,
+};
+use chess::{Game, GameStatus, Player};
+
+use crate::menu::ButtonColumn;
+use crate::net::Network;
+use crate::turn::Controllers;
+
+/// Pushed on top of `MyState` once nobody can move anymore.
+/// The board stays visible underneath, only the result is drawn over it.
+pub struct GameOverState {
+    pub status: GameStatus,
+    buttons: Option<ButtonColumn>,
+}
+
+impl GameOverState {
+    pub fn new(status: GameStatus) -> GameOverState {
+        GameOverState {
+            status,
+            buttons: None,
+        }
+    }
+
+    fn result_text(&self) -> String {
+        match self.status {
+            GameStatus::Checkmate(Player::FIRST) => "Checkmate - white wins".to_string(),
+            GameStatus::Checkmate(_) => "Checkmate - black wins".to_string(),
+            GameStatus::Stalemate => "Stalemate - draw".to_string(),
+            GameStatus::TimeOut(Player::FIRST) => "Time out - white wins".to_string(),
+            GameStatus::TimeOut(_) => "Time out - black wins".to_string(),
+            GameStatus::Resignation(Player::FIRST) => "Black resigns - white wins".to_string(),
+            GameStatus::Resignation(_) => "White resigns - black wins".to_string(),
+            GameStatus::DrawAgreed => "Draw agreed".to_string(),
+            GameStatus::Ongoing => String::new(),
+        }
+    }
+}
+
+impl SimpleState for GameOverState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let buttons = [("menu", "Main menu".to_string()), ("quit", "Quit".to_string())];
+        self.buttons = Some(ButtonColumn::new(data.world, &self.result_text(), &buttons));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(buttons) = self.buttons.take() {
+            buttons.delete(data.world);
+        }
+    }
+
+    /// Escape goes back to the main menu, `undo` takes back the last move
+    /// and returns to the game, unless the game was lost on time or played
+    /// over the network. A winning move of the computer is taken back with
+    /// the move of the human before it.
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Input(InputEvent::ActionPressed(action)) = &event {
+            // neither time nor the other player over the network can be taken back
+            let timeout = matches!(self.status, GameStatus::TimeOut(_));
+            if action == "undo" && !timeout && !data.world.has_value::<Network>() {
+                let controllers = *data.world.read_resource::<Controllers>();
+                let mut game = data.world.write_resource::<Game>();
+                game.back();
+                // a computer that won would only play its move again
+                while controllers.steps_over(game.position().side_to_move()) && game.back() {}
+                return Trans::Pop;
+            }
+        }
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Sequence(vec![Trans::Pop, Trans::Pop]);
+            }
+        }
+        match self.buttons.as_ref().and_then(|buttons| buttons.clicked(&event)) {
+            Some("menu") => Trans::Sequence(vec![Trans::Pop, Trans::Pop]),
+            Some("quit") => Trans::Quit,
+            _ => Trans::None,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use amethyst::ecs::Entity;
+use chess::Player;
+
+/// Which piece stands on each square, indexed as `[x][y]`.
+///
+/// This is the one place the frontend looks up pieces by square. It is
+/// rebuilt by `MyState::sync_pieces` and updated by `MoveSystem::apply`
+/// on every move, so it always matches the piece entities.
+pub struct Grid {
+    /// Width of a square on screen.
+    square_size: f32,
+    players: Vec<Vec<Player>>,
+    entities: Vec<Vec<Option<Entity>>>,
+}
+
+impl Grid {
+    pub fn new(grid_size: (u32, u32), square_size: f32) -> Grid {
+        let (width, height) = (grid_size.0 as usize, grid_size.1 as usize);
+        Grid {
+            square_size,
+            players: vec![vec![Player::NONE; height]; width],
+            entities: vec![vec![None; height]; width],
+        }
+    }
+
+    pub fn square_size(&self) -> f32 {
+        self.square_size
+    }
+
+    /// The square under a point on screen, `None` outside the board.
+    pub fn square_at(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        if x < 0. || y < 0. {
+            return None;
+        }
+        let square = ((x / self.square_size) as u32, (y / self.square_size) as u32);
+        if (square.0 as usize) < self.players.len() && (square.1 as usize) < self.players[0].len() {
+            Some(square)
+        } else {
+            None
+        }
+    }
+
+    /// Owner of the piece on `square`, `Player::NONE` if it is empty.
+    pub fn player(&self, square: (u32, u32)) -> Player {
+        self.players[square.0 as usize][square.1 as usize]
+    }
+
+    pub fn entity(&self, square: (u32, u32)) -> Option<Entity> {
+        self.entities[square.0 as usize][square.1 as usize]
+    }
+
+    pub fn place(&mut self, square: (u32, u32), entity: Entity, player: Player) {
+        self.players[square.0 as usize][square.1 as usize] = player;
+        self.entities[square.0 as usize][square.1 as usize] = Some(entity);
+    }
+
+    /// Empties `square` and returns the entity that stood there.
+    pub fn remove(&mut self, square: (u32, u32)) -> Option<Entity> {
+        self.players[square.0 as usize][square.1 as usize] = Player::NONE;
+        self.entities[square.0 as usize][square.1 as usize].take()
+    }
+
+    /// Moves whatever stands on `from` to `to` and returns its entity.
+    pub fn move_piece(&mut self, from: (u32, u32), to: (u32, u32)) -> Option<Entity> {
+        let player = self.player(from);
+        let entity = self.remove(from)?;
+        self.place(to, entity, player);
+        Some(entity)
+    }
+
+    /// Empties every square.
+    pub fn clear(&mut self) {
+        for column in self.players.iter_mut() {
+            for player in column.iter_mut() {
+                *player = Player::NONE;
+            }
+        }
+        for column in self.entities.iter_mut() {
+            for entity in column.iter_mut() {
+                *entity = None;
+            }
+        }
+    }
+}
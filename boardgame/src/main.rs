@@ -1,5 +1,5 @@
-use crate::player::Player;
 use amethyst::{
+    config::Config,
     core::transform::TransformBundle,
     input::{InputBundle, StringBindings},
     prelude::*,
@@ -11,13 +11,36 @@ use amethyst::{
     ui::{RenderUi, UiBundle},
     utils::application_root_dir,
 };
+use chess::{BitPosition, Position, Rules};
+use std::path::PathBuf;
+use std::time::Instant;
+use structopt::StructOpt;
+mod ai;
+mod badge;
+mod clock;
+mod color_piece;
+mod config;
+mod game_over;
+mod grid;
+mod menu;
+mod mode;
 mod move_system;
+mod net;
+mod pause;
 mod piece;
-mod player;
+mod save;
 mod state;
+mod turn;
+mod tween;
+mod variant;
+mod view;
 
-//mod color_piece;
 fn main() -> amethyst::Result<()> {
+    let opts = Opt::from_args();
+    if let Some(Command::Perft { depth, fen }) = opts.command {
+        return perft(depth, fen);
+    }
+
     amethyst::start_logger(Default::default());
 
     let app_root = application_root_dir()?;
@@ -25,6 +48,17 @@ fn main() -> amethyst::Result<()> {
     let resources = app_root.join("assets");
     let display_config = app_root.join("config/display_config.ron");
     let key_bindings_path = app_root.join("config/input.ron");
+    let mut game_config = config::GameConfig::load(app_root.join("config/game.ron"))?;
+
+    if let Some(address) = opts.host {
+        game_config.net.host = address;
+    }
+    if let Some(address) = opts.join {
+        game_config.net.join = address;
+    }
+    if let Some(address) = opts.watch {
+        game_config.net.watch = address;
+    }
 
     let game_data = GameDataBuilder::default()
         .with_bundle(TransformBundle::new())?
@@ -40,21 +74,73 @@ fn main() -> amethyst::Result<()> {
                 )
                 .with_plugin(RenderUi::default())
                 .with_plugin(RenderFlat2D::default()),
-        )?
-        .with(
-            move_system::MoveSystem::default(),
-            "king_movement_system",
-            &["input_system"],
-        );
-    //.with(color_piece::ColorPieceSystem,"color_piece_system",&[]);
-
-    let gamestate = state::MyState {
-        grid_size: (8, 8),
-        sprite_size: 100.,
-        grid: vec![vec![Player::NONE]],
-    };
-    let mut game = Application::new(resources, gamestate, game_data)?;
+        )?;
+
+    let menu = menu::MenuState::new(opts.fen, opts.pgn);
+    let mut game = Application::build(resources, menu)?
+        .with_resource(game_config)
+        .build(game_data)?;
     game.run();
 
     Ok(())
 }
+
+/// Chess board written with amethyst.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "boardgame")]
+struct Opt {
+    /// Position for "Load FEN" in Forsyth-Edwards Notation, overrides config/game.ron
+    #[structopt(long)]
+    fen: Option<String>,
+    /// PGN file for "Load PGN", step through it with the arrow keys
+    #[structopt(long, parse(from_os_str))]
+    pgn: Option<PathBuf>,
+    /// Address to wait on for "Host network game", overrides config/game.ron
+    #[structopt(long)]
+    host: Option<String>,
+    /// Address of the host for "Join network game", overrides config/game.ron
+    #[structopt(long)]
+    join: Option<String>,
+    /// Address of the relay for "Watch network game", overrides config/game.ron
+    #[structopt(long)]
+    watch: Option<String>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Counts the move sequences of the given length to check the move generator
+    Perft {
+        depth: u32,
+        /// Position to count from, the starting position if not given
+        fen: Option<String>,
+    },
+}
+
+/// Prints the perft count split up by the first move, like other engines
+/// do, so the numbers can be compared move by move.
+fn perft(depth: u32, fen: Option<String>) -> amethyst::Result<()> {
+    let position = match fen {
+        Some(fen) => Position::from_fen(&fen)?,
+        None => Position::standard(),
+    };
+    let start = Instant::now();
+    let mut total = position.perft(0);
+    if depth > 0 {
+        // the bitboards are much faster but only know the 8x8 board
+        let divided = match BitPosition::from_position(&position) {
+            Some(bits) => bits.divide(depth),
+            None => position.divide(depth),
+        };
+        total = 0;
+        for (mv, nodes) in divided {
+            println!("{}: {}", mv.to_uci(), nodes);
+            total += nodes;
+        }
+    }
+    println!();
+    println!("Nodes searched: {}", total);
+    println!("Time: {:.3}s", start.elapsed().as_secs_f64());
+    Ok(())
+}
@@ -0,0 +1,423 @@
+use amethyst::{
+    assets::Loader,
+    config::Config,
+    ecs::Entity,
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{
+        Anchor, LineMode, TtfFormat, UiButton, UiButtonBuilder, UiEvent, UiEventType, UiText,
+        UiTransform,
+    },
+};
+use chess::{Game, Pgn};
+use log::error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::GameConfig;
+use crate::mode::Mode;
+use crate::net::{ConnectState, Role};
+use crate::save::SavedGame;
+use crate::state::MyState;
+use crate::turn::{Controller, Controllers};
+
+/// A title with a column of text buttons below it, in the middle of the screen.
+pub struct ButtonColumn {
+    title: Entity,
+    /// Each button with the name `clicked` reports for it.
+    buttons: Vec<(&'static str, UiButton)>,
+}
+
+impl ButtonColumn {
+    pub fn new(world: &mut World, title: &str, buttons: &[(&'static str, String)]) -> ButtonColumn {
+        let font = world.read_resource::<Loader>().load(
+            "fonts/Bangers-Regular.ttf",
+            TtfFormat,
+            (),
+            &world.read_resource(),
+        );
+        let title = world
+            .create_entity()
+            .with(UiTransform::new(
+                "title".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                250.,
+                5.,
+                600.,
+                90.,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                title.to_string(),
+                [1., 0.8, 0.2, 1.],
+                70.,
+                LineMode::Single,
+                Anchor::Middle,
+            ))
+            .build();
+        let buttons = buttons
+            .iter()
+            .enumerate()
+            .map(|(i, (name, label))| {
+                let (_, button) = UiButtonBuilder::<(), u32>::new(label.clone())
+                    .with_font(font.clone())
+                    .with_font_size(40.)
+                    .with_text_color([1., 1., 1., 1.])
+                    .with_hover_text_color([1., 0.8, 0.2, 1.])
+                    .with_anchor(Anchor::Middle)
+                    .with_position(0., 150. - 55. * i as f32)
+                    .with_size(500., 50.)
+                    .build_from_world(world);
+                (*name, button)
+            })
+            .collect();
+        ButtonColumn { title, buttons }
+    }
+
+    /// Name of the button the event clicked, if any.
+    pub fn clicked(&self, event: &StateEvent) -> Option<&'static str> {
+        match event {
+            StateEvent::Ui(UiEvent {
+                event_type: UiEventType::Click,
+                target,
+            }) => self
+                .buttons
+                .iter()
+                .find(|(_, button)| button.image_entity == *target || button.text_entity == *target)
+                .map(|(name, _)| *name),
+            _ => None,
+        }
+    }
+
+    pub fn set_label(&self, world: &mut World, name: &str, label: String) {
+        let button = self.buttons.iter().find(|(n, _)| *n == name);
+        if let Some((_, button)) = button {
+            if let Some(text) = world.write_storage::<UiText>().get_mut(button.text_entity) {
+                text.text = label;
+            }
+        }
+    }
+
+    pub fn delete(self, world: &mut World) {
+        let mut entities = vec![self.title];
+        for (_, button) in self.buttons {
+            entities.push(button.text_entity);
+            entities.push(button.image_entity);
+        }
+        world
+            .delete_entities(&entities)
+            .expect("failed to delete menu");
+    }
+}
+
+/// The first screen: start a game against a human or the computer, load
+/// one, play or watch over the network, change the settings or quit. Games are pushed on top and pop back here.
+pub struct MenuState {
+    /// Position to start from for "Load FEN", from the command line.
+    pub fen: Option<String>,
+    /// Game to replay for "Load PGN", from the command line.
+    pub pgn: Option<PathBuf>,
+    buttons: Option<ButtonColumn>,
+}
+
+impl MenuState {
+    pub fn new(fen: Option<String>, pgn: Option<PathBuf>) -> MenuState {
+        MenuState {
+            fen,
+            pgn,
+            buttons: None,
+        }
+    }
+
+    fn show(&mut self, world: &mut World) {
+        let mut buttons = vec![];
+        if save_path(world).map_or(false, |path| path.exists()) {
+            buttons.push(("continue", "Continue last game".to_string()));
+        }
+        buttons.extend(vec![
+            ("human", "New game vs human".to_string()),
+            ("computer", "New game vs computer".to_string()),
+            ("fen", "Load FEN".to_string()),
+            ("pgn", "Load PGN".to_string()),
+            ("host", "Host network game".to_string()),
+            ("join", "Join network game".to_string()),
+            ("watch", "Watch network game".to_string()),
+            ("settings", "Settings".to_string()),
+            ("quit", "Quit".to_string()),
+        ]);
+        self.buttons = Some(ButtonColumn::new(world, "Chess", &buttons));
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        if let Some(buttons) = self.buttons.take() {
+            buttons.delete(world);
+        }
+    }
+
+    /// The state playing or replaying the chosen game, `None` if it could not be loaded.
+    fn start(&self, world: &World, choice: &str) -> Option<MyState> {
+        let mut config = world.read_resource::<GameConfig>().clone();
+        let (game, mode) = match choice {
+            "continue" => {
+                let path = save_path(world)?;
+                let saved = SavedGame::load(&path).map_err(|err| err.to_string());
+                return match saved.and_then(|saved| saved.game().map(|game| (saved, game))) {
+                    Ok((saved, game)) => Some(
+                        MyState::new(game, Mode::Play, saved.config(&config))
+                            .with_remaining(saved.remaining()),
+                    ),
+                    Err(err) => {
+                        error!("cannot continue {}: {}", path.display(), err);
+                        None
+                    }
+                };
+            }
+            "human" | "computer" => {
+                if choice == "human" {
+                    config.controllers = Controllers::default();
+                } else if config.controllers == Controllers::default() {
+                    // the configured opponent, or the built-in one if both are human
+                    config.controllers.second = Controller::Ai;
+                }
+                match config.variant.start() {
+                    Ok(position) => (Game::new(position), Mode::Play),
+                    Err(err) => {
+                        error!("cannot start the variant: {}", err);
+                        return None;
+                    }
+                }
+            }
+            "fen" => {
+                // a FEN given on the command line wins over the one in the config
+                let fen = match self.fen.clone().or_else(|| config.fen.clone()) {
+                    Some(fen) => fen,
+                    None => {
+                        error!("no FEN given, pass --fen or set fen in config/game.ron");
+                        return None;
+                    }
+                };
+                match config.variant.position(&fen) {
+                    Ok(position) => (Game::new(position), Mode::Play),
+                    Err(err) => {
+                        error!("invalid FEN {}: {}", fen, err);
+                        return None;
+                    }
+                }
+            }
+            "pgn" => {
+                // the file from the command line or the last saved game
+                let path = match &self.pgn {
+                    Some(path) => path.clone(),
+                    None => amethyst::utils::application_root_dir()
+                        .ok()?
+                        .join(&config.pgn_path),
+                };
+                let pgn = fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|text| Pgn::parse(&text).map_err(|err| err.to_string()));
+                match pgn {
+                    Ok(pgn) => {
+                        let mut game = pgn.game;
+                        game.rewind();
+                        (game, Mode::Replay)
+                    }
+                    Err(err) => {
+                        error!("cannot load {}: {}", path.display(), err);
+                        return None;
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(MyState::new(game, mode, config))
+    }
+}
+
+impl SimpleState for MenuState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+        }
+        let clicked = match self.buttons.as_ref().and_then(|buttons| buttons.clicked(&event)) {
+            Some(clicked) => clicked,
+            None => return Trans::None,
+        };
+        match clicked {
+            "settings" => Trans::Push(Box::new(SettingsState::default())),
+            "host" => Trans::Push(Box::new(ConnectState::new(Role::Host))),
+            "join" => Trans::Push(Box::new(ConnectState::new(Role::Join))),
+            "watch" => Trans::Push(Box::new(ConnectState::new(Role::Watch))),
+            "quit" => Trans::Quit,
+            choice => match self.start(data.world, choice) {
+                Some(state) => Trans::Push(Box::new(state)),
+                None => Trans::None,
+            },
+        }
+    }
+}
+
+/// Where the last unfinished game was saved.
+fn save_path(world: &World) -> Option<PathBuf> {
+    let root = amethyst::utils::application_root_dir().ok()?;
+    Some(root.join(&world.read_resource::<GameConfig>().save_path))
+}
+
+/// Changes the `GameConfig` for the games started from the menu. The
+/// changes last until the program ends, `config/game.ron` stays as it is.
+#[derive(Default)]
+pub struct SettingsState {
+    buttons: Option<ButtonColumn>,
+}
+
+impl SettingsState {
+    fn clock_label(config: &GameConfig) -> String {
+        match &config.clock.selected {
+            Some(name) => format!("Clock: {}", name),
+            None => "Clock: off".to_string(),
+        }
+    }
+
+    fn depth_label(config: &GameConfig) -> String {
+        format!("Computer depth: {}", config.ai.depth)
+    }
+
+    fn flip_label(config: &GameConfig) -> String {
+        let flip = if config.auto_flip { "on" } else { "off" };
+        format!("Flip board after moves: {}", flip)
+    }
+
+    fn variant_label(config: &GameConfig) -> String {
+        match &config.variant.selected {
+            Some(name) => format!("Variant: {}", name),
+            None => "Variant: standard".to_string(),
+        }
+    }
+}
+
+impl SimpleState for SettingsState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let (clock, depth, flip, variant) = {
+            let config = data.world.read_resource::<GameConfig>();
+            (
+                Self::clock_label(&config),
+                Self::depth_label(&config),
+                Self::flip_label(&config),
+                Self::variant_label(&config),
+            )
+        };
+        let buttons = [
+            ("clock", clock),
+            ("depth", depth),
+            ("flip", flip),
+            ("variant", variant),
+            ("back", "Back".to_string()),
+        ];
+        self.buttons = Some(ButtonColumn::new(data.world, "Settings", &buttons));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(buttons) = self.buttons.take() {
+            buttons.delete(data.world);
+        }
+    }
+
+    /// Clicking the clock goes through the time controls and playing
+    /// without clocks, clicking the depth through 1 to 8 plies. Flipping
+    /// the board is switched on and off. The variant goes through the
+    /// variants and standard chess.
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+        }
+        let buttons = match &self.buttons {
+            Some(buttons) => buttons,
+            None => return Trans::None,
+        };
+        let world = data.world;
+        match buttons.clicked(&event) {
+            Some("clock") => {
+                let label = {
+                    let mut config = world.write_resource::<GameConfig>();
+                    let names: Vec<String> = config.clock.time_controls.keys().cloned().collect();
+                    config.clock.selected = next_name(&names, &config.clock.selected);
+                    Self::clock_label(&config)
+                };
+                buttons.set_label(world, "clock", label);
+            }
+            Some("depth") => {
+                let label = {
+                    let mut config = world.write_resource::<GameConfig>();
+                    config.ai.depth = config.ai.depth % 8 + 1;
+                    Self::depth_label(&config)
+                };
+                buttons.set_label(world, "depth", label);
+            }
+            Some("flip") => {
+                let label = {
+                    let mut config = world.write_resource::<GameConfig>();
+                    config.auto_flip = !config.auto_flip;
+                    Self::flip_label(&config)
+                };
+                buttons.set_label(world, "flip", label);
+            }
+            Some("variant") => {
+                let label = {
+                    let mut config = world.write_resource::<GameConfig>();
+                    let names: Vec<String> = config.variant.variants.keys().cloned().collect();
+                    config.variant.selected = next_name(&names, &config.variant.selected);
+                    Self::variant_label(&config)
+                };
+                buttons.set_label(world, "variant", label);
+            }
+            Some("back") => return Trans::Pop,
+            _ => {}
+        }
+        Trans::None
+    }
+}
+
+/// The name after `selected` in `names`, `None` after the last one and the
+/// first one after `None`.
+fn next_name(names: &[String], selected: &Option<String>) -> Option<String> {
+    match selected {
+        None => names.first().cloned(),
+        Some(selected) => names
+            .iter()
+            .skip_while(|name| *name != selected)
+            .nth(1)
+            .cloned(),
+    }
+}
@@ -0,0 +1,15 @@
+/// What the window is used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Pieces are moved with the mouse.
+    Play,
+    /// A loaded game is stepped through, moving pieces is disabled.
+    Replay,
+    /// A game relayed over the network is followed, moving pieces is disabled.
+    Watch,
+}
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Play
+    }
+}
@@ -1,18 +1,45 @@
-use crate::piece::{Piece,PieceType};
-use crate::piece::Status;
+use crate::grid::Grid;
+use crate::piece::{sprite_index, Captures, Piece, Status};
+use crate::mode::Mode;
+use crate::tween::{AnimationConfig, Fade, Tween};
+use crate::turn::{Controller, Controllers, Outcome, Prompt, Turn};
+use crate::view::View;
 use amethyst::core::Transform;
-use amethyst::ecs::{Join, Read, ReadExpect, System, WriteStorage};
+use amethyst::ecs::{
+    Entity, Join, Read, ReadExpect, System, Write, WriteExpect, WriteStorage,
+};
 use amethyst::input::{InputHandler, StringBindings};
+use amethyst::renderer::SpriteRender;
 use amethyst::window::ScreenDimensions;
+use chess::{Clock, Game, GameStatus, Move, PieceType, Position};
+use log::{debug, info};
 
+/// Moves not made with the mouse, e.g. by the computer, waiting to be played
+/// by the `MoveSystem`.
+#[derive(Default)]
+pub struct PendingMoves {
+    pub moves: Vec<Move>,
+}
+
+/// Plays the moves of the human players, either by dragging a piece onto
+/// its target square or by clicking the piece and then the square.
 pub struct MoveSystem {
     pressed: bool,
+    /// Square the mouse button went down on.
+    press_square: Option<(u32, u32)>,
+    /// Piece following the mouse and the square it was picked up from.
+    dragging: Option<(Entity, (u32, u32))>,
+    /// A pawn move to the last row waiting for the player to pick a piece.
+    promotion: Option<Move>,
 }
 impl Default for MoveSystem {
     fn default() -> Self {
         Self {
             //pressed is needed to overwrite serveral keyinputs from one keypress
             pressed: false,
+            press_square: None,
+            dragging: None,
+            promotion: None,
         }
     }
 }
@@ -22,106 +49,245 @@ impl<'s> System<'s> for MoveSystem {
         WriteStorage<'s, Transform>,
         WriteStorage<'s, Status>,
         WriteStorage<'s, Piece>,
+        WriteStorage<'s, SpriteRender>,
         Read<'s, InputHandler<StringBindings>>,
         ReadExpect<'s, ScreenDimensions>,
+        WriteExpect<'s, Game>,
+        Write<'s, Captures>,
+        WriteExpect<'s, Turn>,
+        Read<'s, Mode>,
+        Read<'s, Controllers>,
+        Write<'s, PendingMoves>,
+        WriteExpect<'s, Grid>,
+        WriteStorage<'s, Tween>,
+        WriteStorage<'s, Fade>,
+        Read<'s, AnimationConfig>,
+        Option<Read<'s, Clock>>,
+        Read<'s, View>,
+        Write<'s, Prompt>,
+        Write<'s, Outcome>,
     );
 
-    fn run(&mut self, (mut transforms,mut status, mut pieces, inp, dim): Self::SystemData) {
- // 1 check if any piece got clicked.
- // 2 if other piece is selected find out if it can move.
- // 3 replace if 2 gives true
-        let mut selected_pos: Option<(u32,u32)> = None;
-        let mut selected_type: Option<PieceType> = None;
-        let (x, mut y) = inp.mouse_position().unwrap();
-        //y from mouseinput and drawn objects are inverted.
-        y = dim.height() - y;
-        for (piece,st) in (&pieces,&status).join(){
-            match st{
-                Status::Selected => {
-                    selected_pos = Some((piece.x, piece.y));
-                    selected_type = Some(piece.piece_type);
+    fn run(
+        &mut self,
+        (mut transforms, mut status, mut pieces, mut sprites, inp, dim, mut game, mut captures, mut turn, mode, controllers, mut pending, mut grid, mut tweens, mut fades, animation, clock, view, mut prompt, mut outcome): Self::SystemData,
+    ) {
+        // the systems after this one and `MyState` read the status from
+        // here, so it follows every change to the game below as well
+        outcome.update(&game);
+        if *mode == Mode::Replay {
+            return;
+        }
+        // wait until the pieces have arrived
+        if (&tweens).join().next().is_some() {
+            return;
+        }
+        for mv in pending.moves.drain(..) {
+            if game.position().is_legal(mv) {
+                self.apply(mv, &mut game, &mut captures, &mut grid, &mut pieces, &mut transforms, &mut sprites, &mut tweens, &mut fades, &animation);
+                turn.player = game.position().side_to_move();
+            }
+        }
+        outcome.update(&game);
+        // spectators only see the moves arrive
+        if *mode == Mode::Watch {
+            return;
+        }
+        if let Some(mv) = self.promotion {
+            let choice = [
+                ("promote_queen", PieceType::QUEEN),
+                ("promote_rook", PieceType::ROOK),
+                ("promote_bishop", PieceType::BISHOP),
+                ("promote_knight", PieceType::KNIGHT),
+                ("promote_archbishop", PieceType::ARCHBISHOP),
+                ("promote_chancellor", PieceType::CHANCELLOR),
+            ]
+            .iter()
+            .find(|(action, _)| inp.action_is_down(action).unwrap_or(false))
+            .map(|(_, piece_type)| *piece_type);
+            if let Some(piece_type) = choice {
+                let promoted = Move::with_promotion(mv.from, mv.to, piece_type);
+                let moves = self.check(game.position(), mv.from, mv.to);
+                if moves.contains(&promoted) {
+                    self.promotion = None;
+                    prompt.text = None;
+                    self.apply(promoted, &mut game, &mut captures, &mut grid, &mut pieces, &mut transforms, &mut sprites, &mut tweens, &mut fades, &animation);
+                    turn.player = game.position().side_to_move();
+                    for st in (&mut status).join() {
+                        *st = Status::None;
+                    }
+                } else if moves.is_empty() {
+                    // the position changed by an undo in the meantime
+                    self.promotion = None;
+                    prompt.text = None;
+                } else {
+                    // a piece the variant does not have, the choice stays open
+                    let keys = promotion_keys(&moves);
+                    prompt.text = Some(format!("Cannot promote to {}: press {}", piece_type.to_char(), keys));
                 }
-                Status::None => {}
             }
         }
 
-        if self.check(x,y)
-
-
-
-
-
-
-
-        let mut move_piece: Option<&Piece> = None;
-        let mut status = Status::None;
-        for (piece, transform) in (&mut pieces, &mut transforms).join() {
-            let input = inp.action_is_down("press").unwrap_or(false);
-            if input {
+        outcome.update(&game);
+        let mouse = inp.mouse_position().map(|point| view.to_board(point, &dim));
+        let human = controllers.get(turn.player) == Controller::Human
+            && outcome.status == GameStatus::Ongoing
+            && clock.map_or(true, |clock| clock.flagged().is_none());
+        if inp.action_is_down("press").unwrap_or(false) {
+            if !self.pressed {
                 self.pressed = true;
-                println!("input!");
-            }
-            if self.pressed && !input {
-
-                status = match piece.status {
-                    Status::None => {
-                        if piece.is_clicked(x, y) {
-                            self.pressed = false;
-                            Status::Selected
-                        } else {
-                            Status::None
+                // pressing somewhere else while asked for a promotion piece cancels the move
+                self.promotion = None;
+                prompt.text = None;
+                self.press_square = mouse.and_then(|(x, y)| grid.square_at(x, y));
+                // picking up one of the own pieces selects it
+                if let Some(square) = self.press_square.filter(|&square| human && grid.player(square) == turn.player) {
+                    if let Some(entity) = grid.entity(square) {
+                        for st in (&mut status).join() {
+                            *st = Status::None;
                         }
-                    }
-                    Status::Selected => {
-                        self.pressed = false;
-                        if piece.is_clicked(x, y) {
-                            Status::Selected
-                        } else {
-                            //if piece can move to position
-                            if self.check(piece, x, y) {
-                                move_piece = Some(&piece);
-                                Status::None
-                            } else {
-                                Status::Selected
-                            }
+                        if let Some(st) = status.get_mut(entity) {
+                            *st = Status::Selected;
                         }
+                        self.dragging = Some((entity, square));
                     }
-                };
-                if mv {
-                    piece.move_by(x, y);
-                    //transform.prepend_translation(piece.move_by_grid(piece::move_by_xy(x,y,x_piece,y_piece)));
-                    transform.set_translation(piece.get_self_xy());
                 }
             }
+            if let (Some((entity, _)), Some((x, y))) = (self.dragging, mouse) {
+                if let Some(transform) = transforms.get_mut(entity) {
+                    transform.set_translation_xyz(x, y, 2.);
+                }
+            }
+            return;
         }
-        match move_piece {
+        if !self.pressed {
+            return;
+        }
+        self.pressed = false;
+        let dragged = self.dragging.take();
+        let pressed_at = self.press_square.take();
+        let released = mouse.and_then(|(x, y)| grid.square_at(x, y));
 
-            Some(p) => {
-        
-                let moveable = true;
-                for piece in (&mut pieces).join() {
-                    if piece.xy_to_grid(x,y) == (piece.x,piece.y) {    
-                        moveable = false;
-                        p.status = status;
-                        println!("cannot move");
-                    }
-            
+        // a drag onto another square tries that move, letting go where the
+        // button went down counts as a click
+        let mut attempt = None;
+        match (dragged, released) {
+            (Some((_, from)), Some(to)) if to != from => attempt = Some((from, to)),
+            (_, Some(square)) if human && Some(square) == pressed_at => {
+                let selected = (&pieces, &status)
+                    .join()
+                    .find(|(_, st)| matches!(st, Status::Selected))
+                    .map(|(piece, _)| (piece.x, piece.y));
+                match selected {
+                    Some(from) if grid.player(square) != turn.player => attempt = Some((from, square)),
+                    _ => {}
                 }
-                if moveable {
-                    piece.move_by(x, y);
-                    //transform.prepend_translation(piece.move_by_grid(piece::move_by_xy(x,y,x_piece,y_piece)));
-                    transform.set_translation(piece.get_self_xy());
-                    
+            }
+            _ => {}
+        }
 
-            }},
-            None => {},
+        let mut snap_back = dragged.map(|(entity, _)| entity);
+        if let Some((from, to)) = attempt {
+            let moves = self.check(game.position(), from, to);
+            match moves.len() {
+                0 => prompt.text = Some("Cannot move there".to_string()),
+                1 => {
+                    self.apply(moves[0], &mut game, &mut captures, &mut grid, &mut pieces, &mut transforms, &mut sprites, &mut tweens, &mut fades, &animation);
+                    turn.player = game.position().side_to_move();
+                    for st in (&mut status).join() {
+                        *st = Status::None;
+                    }
+                    snap_back = None;
+                }
+                _ => {
+                    prompt.text = Some(format!("Promote: press {}", promotion_keys(&moves)));
+                    self.promotion = Some(Move::new(from, to));
+                }
+            }
+        }
+        if let Some(entity) = snap_back {
+            if let (Some(piece), Some(transform)) = (pieces.get(entity), transforms.get(entity)) {
+                let tween = animation.tween(*transform.translation(), piece.get_self_xy());
+                tweens.insert(entity, tween).expect("failed to animate piece");
+            }
         }
     }
 }
 impl MoveSystem {
-    fn check(&self, piece: &Piece, x: f32, y: f32) -> bool {
+    /// The legal moves from `from` to `to`. More than one means the
+    /// player still has to choose what the pawn promotes to.
+    fn check(&self, position: &Position, from: (u32, u32), to: (u32, u32)) -> Vec<Move> {
+        position
+            .moves_from(from)
+            .into_iter()
+            .filter(|mv| mv.to == to)
+            .collect()
+    }
 
-        let (x_del, y_del) = piece.delta(x, y);
-        (x_del.abs(), y_del.abs()) == (1, 1) || x_del.abs() + y_del.abs() == 1
+    /// Plays `mv` in the game and brings the `Grid` and piece entities in
+    /// line: the captured piece fades out and is recorded, the moved pieces
+    /// slide to their squares, the rook following a castling king, and a
+    /// promoted pawn changes its sprite.
+    fn apply(
+        &self,
+        mv: Move,
+        game: &mut Game,
+        captures: &mut Captures,
+        grid: &mut Grid,
+        pieces: &mut WriteStorage<Piece>,
+        transforms: &mut WriteStorage<Transform>,
+        sprites: &mut WriteStorage<SpriteRender>,
+        tweens: &mut WriteStorage<Tween>,
+        fades: &mut WriteStorage<Fade>,
+        animation: &AnimationConfig,
+    ) {
+        let captured = game.position().capture_square(mv);
+        let mut moved = vec![(mv.from, mv.to)];
+        if let Some(rook) = game.position().castling_rook(mv) {
+            moved.push((rook.from, rook.to));
+        }
+        info!("{}", game.position().to_san(mv));
+        if let Some(piece) = game.play(mv) {
+            debug!("captured {:?} {:?}", piece.player, piece.piece_type);
+            captures.pieces.push(piece);
+        }
+
+        if let Some(entity) = captured.and_then(|square| grid.remove(square)) {
+            // no longer a piece, just a sprite on its way out
+            pieces.remove(entity);
+            fades.insert(entity, animation.fade()).expect("failed to fade captured piece");
+        }
+        for (from, to) in moved {
+            let entity = match grid.move_piece(from, to) {
+                Some(entity) => entity,
+                None => continue,
+            };
+            let (piece, transform, sprite) = match (
+                pieces.get_mut(entity),
+                transforms.get_mut(entity),
+                sprites.get_mut(entity),
+            ) {
+                (Some(piece), Some(transform), Some(sprite)) => (piece, transform, sprite),
+                _ => continue,
+            };
+            piece.x = to.0;
+            piece.y = to.1;
+            let tween = animation.tween(*transform.translation(), piece.get_self_xy());
+            tweens.insert(entity, tween).expect("failed to animate piece");
+            if let Some(board_piece) = game.position().board().get(to) {
+                piece.piece_type = board_piece.piece_type;
+                sprite.sprite_number = sprite_index(board_piece);
+            }
+        }
     }
 }
+
+/// The letters of the pieces `moves` promote to, only those of the variant.
+fn promotion_keys(moves: &[Move]) -> String {
+    let keys: Vec<String> = moves
+        .iter()
+        .filter_map(|mv| mv.promotion)
+        .map(|piece_type| piece_type.to_char().to_string())
+        .collect();
+    keys.join(" ")
+}
@@ -0,0 +1,448 @@
+use crate::config::GameConfig;
+use crate::menu::ButtonColumn;
+use crate::mode::Mode;
+use crate::move_system::PendingMoves;
+use crate::state::MyState;
+use crate::turn::{Controller, Controllers};
+use amethyst::{
+    ecs::{Entity, ReadExpect, System, Write, WriteExpect, WriteStorage},
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::UiText,
+};
+use chess::{Connection, Game, GameStatus, Message, Player, Position};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the host looks for a guest and whether it was cancelled.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Where to host and join games over the network, from `config/game.ron`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetConfig {
+    /// Address to wait for the other player on when hosting.
+    pub host: String,
+    /// Address of the host to join.
+    pub join: String,
+    /// Address of the relay to watch a game on, see `chess::serve_relay`.
+    pub watch: String,
+    /// Whether the host plays white.
+    pub host_white: bool,
+}
+impl Default for NetConfig {
+    fn default() -> Self {
+        NetConfig {
+            host: "0.0.0.0:7878".to_string(),
+            join: "127.0.0.1:7878".to_string(),
+            watch: "127.0.0.1:7879".to_string(),
+            host_white: true,
+        }
+    }
+}
+
+/// A game against another machine, shared by `MyState` and the `NetSystem`.
+pub struct Network {
+    /// The side played on this machine, `Player::NONE` when watching.
+    pub side: Player,
+    /// Messages for the `NetSystem` to send.
+    pub outbox: Vec<Message>,
+    /// Side whose draw offer is not answered yet. Any move declines it.
+    pub draw_offer: Option<Player>,
+    /// How the game ended if a player resigned or both agreed to a draw.
+    pub ended: Option<GameStatus>,
+}
+impl Network {
+    pub fn new(side: Player) -> Network {
+        Network {
+            side,
+            outbox: vec![],
+            draw_offer: None,
+            ended: None,
+        }
+    }
+
+    /// Resigns the game for this side.
+    pub fn resign(&mut self) {
+        if self.ended.is_none() && self.side != Player::NONE {
+            self.outbox.push(Message::Resign);
+            self.ended = Some(GameStatus::Resignation(self.side.opponent()));
+        }
+    }
+
+    /// Offers a draw, or accepts the one of the opponent.
+    pub fn offer_draw(&mut self) {
+        if self.ended.is_some() || self.side == Player::NONE {
+            return;
+        }
+        if self.draw_offer == Some(self.side.opponent()) {
+            self.outbox.push(Message::DrawAccept);
+            self.ended = Some(GameStatus::DrawAgreed);
+        } else if self.draw_offer.is_none() {
+            self.outbox.push(Message::DrawOffer);
+            self.draw_offer = Some(self.side);
+        }
+    }
+}
+
+/// The ui entities of the chat: the line to type into and the last line
+/// sent or received above it.
+pub struct ChatText {
+    pub input: Entity,
+    pub last: Entity,
+}
+
+/// Sends the moves made on this machine and hands the moves of the other
+/// player to the `MoveSystem` through `PendingMoves`, after checking them
+/// against the rules. Messages arrive on their own thread so the frame
+/// loop never waits for the network.
+pub struct NetSystem {
+    connection: Connection,
+    messages: Receiver<io::Result<Message>>,
+    /// Ply of the game in the last frame, to notice moves.
+    ply: Option<usize>,
+    /// False once the connection failed, nothing is sent or received after.
+    connected: bool,
+}
+
+impl NetSystem {
+    pub fn new(connection: Connection) -> NetSystem {
+        let (sender, messages) = channel();
+        match connection.try_clone() {
+            Ok(mut reader) => {
+                thread::spawn(move || loop {
+                    let message = reader.receive();
+                    let failed = message.is_err();
+                    // the receiver is gone once the game was closed
+                    if sender.send(message).is_err() || failed {
+                        return;
+                    }
+                });
+            }
+            Err(err) => {
+                let _ = sender.send(Err(err));
+            }
+        }
+        NetSystem {
+            connection,
+            messages,
+            ply: None,
+            connected: true,
+        }
+    }
+
+    /// Handles one message of the other player. Returns an error for
+    /// messages breaking the rules, which ends the connection.
+    fn receive(
+        &self,
+        message: Message,
+        game: &Game,
+        network: &mut Network,
+        pending: &mut PendingMoves,
+    ) -> Result<Option<String>, String> {
+        if network.ended.is_some() {
+            return Ok(None);
+        }
+        match message {
+            Message::Move(mv) => {
+                // moves may still wait for the pieces of the last one to arrive
+                let mut position = game.position().clone();
+                for &waiting in &pending.moves {
+                    position.make_move(waiting);
+                }
+                if position.side_to_move() == network.side {
+                    return Err(format!("opponent moved out of turn: {}", mv.to_uci()));
+                }
+                if !position.is_legal(mv) {
+                    return Err(format!("opponent sent illegal move {}", mv.to_uci()));
+                }
+                pending.moves.push(mv);
+                Ok(None)
+            }
+            Message::Resign => {
+                network.ended = Some(GameStatus::Resignation(network.side));
+                Ok(Some("Opponent resigned".to_string()))
+            }
+            Message::DrawOffer => {
+                network.draw_offer = Some(network.side.opponent());
+                Ok(Some("Opponent offers a draw".to_string()))
+            }
+            Message::DrawAccept if network.draw_offer == Some(network.side) => {
+                network.ended = Some(GameStatus::DrawAgreed);
+                Ok(Some("Opponent accepted the draw".to_string()))
+            }
+            Message::DrawAccept => Err("opponent accepted a draw nobody offered".to_string()),
+            // spectators get the chat of both players with their names
+            Message::Chat(text) if network.side == Player::NONE => Ok(Some(text)),
+            Message::Chat(text) => Ok(Some(format!("Opponent: {}", text))),
+            Message::Ended(status) => {
+                network.ended = Some(status);
+                Ok(None)
+            }
+            Message::Hello { .. } | Message::Moves(_) => {
+                Err("unexpected greeting in the middle of the game".to_string())
+            }
+        }
+    }
+}
+
+impl<'s> System<'s> for NetSystem {
+    type SystemData = (
+        ReadExpect<'s, Game>,
+        Write<'s, PendingMoves>,
+        WriteExpect<'s, Network>,
+        ReadExpect<'s, ChatText>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (game, mut pending, mut network, chat, mut ui_texts): Self::SystemData) {
+        if !self.connected {
+            return;
+        }
+        let mut lines = vec![];
+
+        let ply = game.ply();
+        if self.ply.map_or(false, |last| ply == last + 1) {
+            // our own move, the ones of the opponent arrived over the network
+            if game.position().side_to_move().opponent() == network.side {
+                if let Some(mv) = game.last_move() {
+                    network.outbox.push(Message::Move(mv));
+                }
+            }
+            network.draw_offer = None;
+        }
+        self.ply = Some(ply);
+
+        let mut failure = None;
+        for message in network.outbox.drain(..) {
+            if let Message::Chat(text) = &message {
+                lines.push(format!("You: {}", text));
+            }
+            if let Err(err) = self.connection.send(&message) {
+                failure = Some(err.to_string());
+                break;
+            }
+        }
+        while failure.is_none() {
+            let message = match self.messages.try_recv() {
+                Ok(Ok(message)) => message,
+                Ok(Err(err)) => {
+                    failure = Some(err.to_string());
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    failure = Some("connection closed".to_string());
+                    break;
+                }
+            };
+            match self.receive(message, &game, &mut network, &mut pending) {
+                Ok(line) => lines.extend(line),
+                Err(err) => failure = Some(err),
+            }
+        }
+        if let Some(failure) = failure {
+            error!("lost the connection: {}", failure);
+            lines.push(format!("Connection lost: {}", failure));
+            self.connected = false;
+        }
+
+        if let Some(line) = lines.pop() {
+            info!("{}", line);
+            if let Some(text) = ui_texts.get_mut(chat.last) {
+                text.text = line;
+            }
+        }
+    }
+}
+
+/// Whether this machine waits for the other player, goes to meet it, or
+/// only watches a relayed game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Host,
+    Join,
+    Watch,
+}
+
+/// Shown while connecting to the other player, switches to the game once
+/// both agreed on it. Games over the network are played without clocks,
+/// the times would differ on both machines.
+pub struct ConnectState {
+    role: Role,
+    /// The connection and the game as far as it has been played.
+    connection: Option<Receiver<io::Result<(Connection, Game)>>>,
+    /// Set when leaving, so the host stops waiting and frees its port.
+    cancelled: Arc<AtomicBool>,
+    buttons: Option<ButtonColumn>,
+}
+
+impl ConnectState {
+    pub fn new(role: Role) -> ConnectState {
+        ConnectState {
+            role,
+            connection: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            buttons: None,
+        }
+    }
+}
+
+/// Waits for a guest on `address` until one comes or `cancelled` is set,
+/// then drops the listener. Sends nothing when cancelled.
+fn host(
+    address: &str,
+    start: Position,
+    guest: Player,
+    cancelled: &AtomicBool,
+    sender: &Sender<io::Result<(Connection, Game)>>,
+) {
+    let listener = match TcpListener::bind(address).and_then(|listener| {
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    }) {
+        Ok(listener) => listener,
+        Err(err) => {
+            let _ = sender.send(Err(err));
+            return;
+        }
+    };
+    while !cancelled.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let connection = stream
+                    .set_nonblocking(false)
+                    .and_then(|()| Connection::offer(stream, &start, guest))
+                    .map(|connection| (connection, Game::new(start)));
+                let _ = sender.send(connection);
+                return;
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL),
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                return;
+            }
+        }
+    }
+}
+
+impl SimpleState for ConnectState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let (net, start) = {
+            let config = data.world.read_resource::<GameConfig>();
+            let start = match &config.fen {
+                Some(fen) => config.variant.position(fen),
+                None => config.variant.start(),
+            };
+            (config.net.clone(), start)
+        };
+        let (sender, receiver) = channel();
+        let title = match self.role {
+            Role::Host => {
+                let start = start.unwrap_or_else(|err| {
+                    error!("invalid start in the config, starting from the usual position: {}", err);
+                    Position::standard()
+                });
+                let guest = if net.host_white { Player::SECOND } else { Player::FIRST };
+                let address = net.host.clone();
+                self.cancelled = Arc::new(AtomicBool::new(false));
+                let cancelled = Arc::clone(&self.cancelled);
+                thread::spawn(move || host(&address, start, guest, &cancelled, &sender));
+                format!("Waiting on {}", net.host)
+            }
+            Role::Join => {
+                let address = net.join.clone();
+                thread::spawn(move || {
+                    let _ = sender.send(Connection::connect(&address).map(|connection| {
+                        let game = Game::new(connection.start.clone());
+                        (connection, game)
+                    }));
+                });
+                format!("Joining {}", net.join)
+            }
+            Role::Watch => {
+                let address = net.watch.clone();
+                thread::spawn(move || {
+                    let _ = sender.send(Connection::watch(&address));
+                });
+                format!("Watching {}", net.watch)
+            }
+        };
+        self.connection = Some(receiver);
+        let buttons = [("cancel", "Cancel".to_string())];
+        self.buttons = Some(ButtonColumn::new(data.world, &title, &buttons));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.connection = None;
+        if let Some(buttons) = self.buttons.take() {
+            buttons.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+        }
+        match self.buttons.as_ref().and_then(|buttons| buttons.clicked(&event)) {
+            Some("cancel") => Trans::Pop,
+            _ => Trans::None,
+        }
+    }
+
+    /// Replaces itself with the game once connected, so leaving the game
+    /// goes straight back to the menu.
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let result = match self.connection.as_ref().map(Receiver::try_recv) {
+            Some(Ok(result)) => result,
+            Some(Err(TryRecvError::Empty)) => return Trans::None,
+            Some(Err(TryRecvError::Disconnected)) | None => return Trans::Pop,
+        };
+        let (connection, game) = match result {
+            Ok(connected) => connected,
+            Err(err) => {
+                error!("failed to connect: {}", err);
+                return Trans::Pop;
+            }
+        };
+        let mut config = data.world.read_resource::<GameConfig>().clone();
+        config.clock.selected = None;
+        config.controllers = match connection.side {
+            Player::FIRST => Controllers {
+                first: Controller::Human,
+                second: Controller::Remote,
+            },
+            Player::SECOND => Controllers {
+                first: Controller::Remote,
+                second: Controller::Human,
+            },
+            _ => Controllers {
+                first: Controller::Remote,
+                second: Controller::Remote,
+            },
+        };
+        let mode = match self.role {
+            Role::Watch => Mode::Watch,
+            _ => Mode::Play,
+        };
+        let state = MyState::new(game, mode, config).with_connection(connection);
+        Trans::Switch(Box::new(state))
+    }
+}
@@ -0,0 +1,52 @@
+use amethyst::{
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+};
+
+use crate::menu::ButtonColumn;
+
+/// Pushed over the game with Escape. The game's systems only run while
+/// `MyState` is on top, so the board and the clocks stand still meanwhile.
+#[derive(Default)]
+pub struct PauseState {
+    buttons: Option<ButtonColumn>,
+}
+
+impl SimpleState for PauseState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let buttons = [
+            ("resume", "Resume".to_string()),
+            ("menu", "Main menu".to_string()),
+            ("quit", "Quit".to_string()),
+        ];
+        self.buttons = Some(ButtonColumn::new(data.world, "Paused", &buttons));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(buttons) = self.buttons.take() {
+            buttons.delete(data.world);
+        }
+    }
+
+    /// Escape resumes the game, "Main menu" leaves it.
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+        }
+        match self.buttons.as_ref().and_then(|buttons| buttons.clicked(&event)) {
+            Some("resume") => Trans::Pop,
+            Some("menu") => Trans::Sequence(vec![Trans::Pop, Trans::Pop]),
+            Some("quit") => Trans::Quit,
+            _ => Trans::None,
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use crate::player::Player;
+use amethyst::assets::Handle;
 use amethyst::ecs::{Component, DenseVecStorage};
+use amethyst::renderer::SpriteSheet;
+use chess::{PieceType, Player};
 use nalgebra::base::Vector3;
 
+/// Ties a sprite entity to its square on the `chess::Position`.
 pub struct Piece {
     pub x: u32,
     pub y: u32,
@@ -27,27 +30,6 @@ impl Piece {
             1.,
         )
     }
-
-    pub fn is_clicked(&self, x_click: f32, y_click: f32) -> bool {
-        self.xy_to_grid(x_click, y_click) == (self.x, self.y)
-    }
-    pub fn delta(&self, x_click: f32, y_click: f32) -> (i32, i32) {
-        let (x_click, y_click) = self.xy_to_grid(x_click, y_click);
-        (
-            (self.x as i32 - x_click as i32) as i32,
-            (self.y as i32 - y_click as i32) as i32,
-        )
-    }
-
-    pub fn xy_to_grid(&self, x: f32, y: f32) -> (u32, u32) {
-        ((x / self.size) as u32, y as u32 / self.size as u32)
-    }
-
-    pub fn move_by(&mut self, x_click: f32, y_click: f32) {
-        let (x_new, y_new) = self.xy_to_grid(x_click, y_click);
-        self.x = x_new;
-        self.y = y_new;
-    }
 }
 impl Component for Piece {
     type Storage = DenseVecStorage<Self>;
@@ -60,11 +42,33 @@ pub enum Status {
 impl Component for Status {
     type Storage = DenseVecStorage<Self>;
 }
-pub enum PieceType {
-    KING,
-    QUEEN,
-    ROOK,
-    KNIGHT,
-    BISHOP,
-    PAWN,
+
+/// Sprite sheet the piece entities are drawn from.
+pub struct PieceSprites {
+    pub sheet: Handle<SpriteSheet>,
+}
+
+/// Pieces taken off the board so far, in the order they were captured.
+#[derive(Default)]
+pub struct Captures {
+    pub pieces: Vec<chess::Piece>,
+}
+
+/// Index into `chesspiecesarray.png`: the black pieces fill the top row,
+/// the white ones the row below in the same order. The sheet has no
+/// archbishop and chancellor, they borrow the bishop and the rook and get
+/// a badge from the `BadgeSystem`.
+pub fn sprite_index(piece: chess::Piece) -> usize {
+    let column = match piece.piece_type {
+        PieceType::QUEEN => 0,
+        PieceType::KING => 1,
+        PieceType::ROOK | PieceType::CHANCELLOR => 2,
+        PieceType::KNIGHT => 3,
+        PieceType::BISHOP | PieceType::ARCHBISHOP => 4,
+        PieceType::PAWN => 5,
+    };
+    match piece.player {
+        Player::FIRST => column + 6,
+        _ => column,
+    }
 }
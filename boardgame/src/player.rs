@@ -1,6 +0,0 @@
-#[derive(Clone)]
-pub enum Player {
-    FIRST,
-    SECOND,
-    NONE,
-}
@@ -0,0 +1,99 @@
+use crate::clock::ClockConfig;
+use crate::config::{AiConfig, GameConfig};
+use crate::turn::Controllers;
+use crate::variant::castling_distance;
+use chess::{Clock, Game, PieceSet, Player, Position};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// An unfinished game as written to the save file from the config, to be
+/// continued from the main menu. Moves are kept in UCI notation so the
+/// file can be read and edited by hand.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SavedGame {
+    /// Position the game started from in FEN.
+    pub start: String,
+    /// Letters of the pieces pawns promote to in the variant played, e.g. `QRBN`.
+    pub promotions: String,
+    /// Whether pawns may move two squares from their first row.
+    pub double_step: bool,
+    /// Squares the king moves when castling.
+    #[serde(default = "castling_distance")]
+    pub castling_distance: u32,
+    /// All moves, including taken back ones that can still be redone.
+    pub moves: Vec<String>,
+    /// Number of moves played on the board.
+    pub ply: usize,
+    pub white: String,
+    pub black: String,
+    pub controllers: Controllers,
+    pub ai: AiConfig,
+    pub clock: ClockConfig,
+    /// Time left for white and black in milliseconds, if playing with clocks.
+    pub remaining_ms: Option<(u64, u64)>,
+}
+
+impl SavedGame {
+    pub fn new(game: &Game, config: &GameConfig, clock: Option<&Clock>) -> SavedGame {
+        SavedGame {
+            start: game.start().to_fen(),
+            promotions: game.start().promotions().to_string(),
+            double_step: game.start().double_step(),
+            castling_distance: game.start().castling_distance(),
+            moves: game.moves().iter().map(|mv| mv.to_uci()).collect(),
+            ply: game.ply(),
+            white: config.white.clone(),
+            black: config.black.clone(),
+            controllers: config.controllers,
+            ai: config.ai,
+            clock: config.clock.clone(),
+            remaining_ms: clock.map(|clock| {
+                (
+                    clock.remaining(Player::FIRST).as_millis() as u64,
+                    clock.remaining(Player::SECOND).as_millis() as u64,
+                )
+            }),
+        }
+    }
+
+    /// The game as it was saved, every move checked against the rules again.
+    pub fn game(&self) -> Result<Game, String> {
+        let mut start = Position::from_fen(&self.start).map_err(|err| err.to_string())?;
+        let promotions = PieceSet::parse(&self.promotions)
+            .ok_or_else(|| format!("unknown piece in promotions {}", self.promotions))?;
+        start.set_promotions(promotions);
+        start.set_double_step(self.double_step);
+        if self.castling_distance < 2 {
+            return Err(format!("castling distance {} in saved game is below 2", self.castling_distance));
+        }
+        start.set_castling_distance(self.castling_distance);
+        let mut game = Game::new(start);
+        for uci in &self.moves {
+            match game.position().parse_uci(uci) {
+                Some(mv) => {
+                    game.play(mv);
+                }
+                None => return Err(format!("illegal move {} in saved game", uci)),
+            }
+        }
+        while game.ply() > self.ply && game.back() {}
+        Ok(game)
+    }
+
+    /// `config` with the players and time control of the saved game.
+    pub fn config(&self, config: &GameConfig) -> GameConfig {
+        let mut config = config.clone();
+        config.white = self.white.clone();
+        config.black = self.black.clone();
+        config.controllers = self.controllers;
+        config.ai = self.ai;
+        config.clock = self.clock.clone();
+        config
+    }
+
+    /// Time left on the clocks of white and black.
+    pub fn remaining(&self) -> Option<(Duration, Duration)> {
+        self.remaining_ms
+            .map(|(first, second)| (Duration::from_millis(first), Duration::from_millis(second)))
+    }
+}
@@ -1,21 +1,64 @@
 use amethyst::{
     assets::{AssetStorage, Handle, Loader},
-    core::transform::Transform,
-    input::{get_key, is_close_requested, is_key_down, VirtualKeyCode},
+    config::Config,
+    core::{transform::Transform, ArcThreadPool},
+    ecs::{Component, Dispatcher, DispatcherBuilder, Entity, Join},
+    input::{get_key, is_close_requested, is_key_down, InputEvent, VirtualKeyCode},
     prelude::*,
     renderer::{Camera, ImageFormat, SpriteRender, SpriteSheet, SpriteSheetFormat, Texture},
+    ui::{
+        Anchor, Interactable, LineMode, Selectable, Selected, TextEditing, TtfFormat, UiText,
+        UiTransform,
+    },
+    utils::application_root_dir,
     window::ScreenDimensions,
 };
+use chrono::Local;
 use nalgebra::base::Vector3;
+use std::fs;
+use std::time::Duration;
 
-use crate::piece::{Piece,PieceType, Status};
-use crate::player::Player;
-use log::info;
+use crate::ai::AiSystem;
+use crate::badge::{Badge, BadgeSystem};
+use crate::clock::{ClockSystem, ClockText, Redone};
+use crate::color_piece::{ColorPieceSystem, Marker, MarkerSprites};
+use crate::config::GameConfig;
+use crate::game_over::GameOverState;
+use crate::grid::Grid;
+use crate::mode::Mode;
+use crate::move_system::{MoveSystem, PendingMoves};
+use crate::net::{ChatText, NetSystem, Network};
+use crate::pause::PauseState;
+use crate::save::SavedGame;
+use crate::piece::{sprite_index, Captures, Piece, PieceSprites, Status};
+use crate::turn::{Controllers, Outcome, Prompt, Turn, TurnSystem, TurnText};
+use crate::tween::{AnimationConfig, Fade, Tween, TweenSystem};
+use crate::view::{View, ViewSystem};
+use chess::{Clock, Connection, Game, GameStatus, Message, PieceType, Pgn, Player};
+use log::{error, info};
 
+/// Plays or replays one game, pushed by the main menu.
 pub struct MyState {
     pub grid_size: (u32, u32),
     pub sprite_size: f32,
-    pub grid: Vec<Vec<Player>>,
+    /// The game to play or replay.
+    pub game: Game,
+    pub mode: Mode,
+    pub config: GameConfig,
+    /// The systems of the game. They only run while this state is on top,
+    /// so the pause and game over screens freeze the board and the clocks.
+    dispatcher: Option<Dispatcher<'static, 'static>>,
+    /// The other player when playing over the network, handed to the
+    /// `NetSystem` on start.
+    connection: Option<Connection>,
+    /// Time left on the clocks of white and black when continuing a saved game.
+    remaining: Option<(Duration, Duration)>,
+    /// The board, camera and texts made on start, deleted on stop together
+    /// with the pieces and what the systems put on the board.
+    entities: Vec<Entity>,
+    /// Set when the game over screen is shown, the game may come back from
+    /// it with moves taken back.
+    resync: bool,
 }
 
 impl SimpleState for MyState {
@@ -30,8 +73,16 @@ impl SimpleState for MyState {
         world.register::<Piece>();
 
         world.register::<Status>();
+        // The board fills as much of the screen as it can, whatever its size
+        let (grid_x, grid_y) = self.grid_size;
+        self.sprite_size = (dimensions.width() / grid_x as f32).min(dimensions.height() / grid_y as f32);
+        let center = (
+            grid_x as f32 * self.sprite_size / 2.,
+            grid_y as f32 * self.sprite_size / 2.,
+        );
         // Place the camera
-        self::MyState::init_camera(world, &dimensions);
+        let camera = self::MyState::init_camera(world, &dimensions, center);
+        self.entities.push(camera);
 
         // Load our sprites and display them
         let background_sprites =
@@ -39,23 +90,111 @@ impl SimpleState for MyState {
         let piece_sprites =
             self.load_sprites(world, "sprites/chesspiecesarray.png", "sprites/chesspiecesarray.ron");
  
+        world.insert(MarkerSprites {
+            sheet: background_sprites.clone(),
+        });
         self.init_background_sprites(world, background_sprites);
+        world.insert(self.mode);
+        world.insert(self.config.controllers);
+        world.insert(self.config.ai);
+        world.insert(self.config.engine.clone());
+        world.insert(Grid::new(self.grid_size, self.sprite_size));
+        world.insert(self.config.animation);
+        world.insert(PendingMoves::default());
+        world.insert(Prompt::default());
+        world.insert(Redone::default());
+        world.insert(Outcome::default());
+        let side = self.connection.as_ref().map(|connection| connection.side);
+        world.insert(View {
+            flipped: side == Some(Player::SECOND),
+            auto_flip: self.config.auto_flip,
+            center,
+        });
         self.init_piece_sprites(world, piece_sprites);
+        self.init_turn_text(world);
+        self.init_clocks(world);
+        if let Some(side) = side {
+            world.insert(Network::new(side));
+            self.init_chat(world);
+        }
+        self.init_dispatcher(world);
+    }
+
+    /// Back to the menu or quitting: saves the game if it is not over yet,
+    /// then removes everything the game put into the world.
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.save_game(data.world);
+        self.dispatcher = None;
+        let mut owned = std::mem::take(&mut self.entities);
+        owned.extend(entities_with::<Piece>(data.world));
+        owned.extend(entities_with::<Badge>(data.world));
+        owned.extend(entities_with::<Marker>(data.world));
+        owned.extend(entities_with::<Fade>(data.world));
+        data.world
+            .delete_entities(&owned)
+            .expect("failed to delete the game");
+        data.world.remove::<Clock>();
+        data.world.remove::<ClockText>();
+        data.world.remove::<Network>();
+        data.world.remove::<ChatText>();
     }
 
     /// The following events are handled:
-    /// - The game state is quit when either the close button is clicked or when the escape key is pressed.
+    /// - The game is quit when the close button is clicked.
+    /// - The escape key pauses the game.
+    /// - The `export_fen` action writes the current position as FEN to the
+    ///   FEN file from the config.
+    /// - The `save_pgn` action writes the game to the PGN file from the config.
+    /// - `undo` and `redo` take back a move and play it again, against the
+    ///   computer together with its reply.
+    /// - `flip` turns the board around.
+    /// - Over the network, `resign` and `offer_draw` end the game and enter
+    ///   sends the chat line. Moves cannot be taken back there.
+    /// - While replaying, `replay_back` and `replay_forward` step through the game.
+    ///   Stepping waits for running animations to finish.
     /// - Any other keypress is simply logged to the console.
     fn handle_event(
         &mut self,
-        mut _data: StateData<'_, GameData<'_, '_>>,
+        data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
+        let world = data.world;
+        // keys typed into the chat are no actions
+        let chatting = world
+            .try_fetch::<ChatText>()
+            .map_or(false, |chat| world.read_storage::<Selected>().contains(chat.input));
+        let networked = world.has_value::<Network>();
+        if let StateEvent::Input(InputEvent::ActionPressed(action)) = &event {
+            if chatting {
+                return Trans::None;
+            }
+            match action.as_str() {
+                "export_fen" => self.export_fen(world),
+                "save_pgn" => self.save_pgn(world),
+                "flip" => {
+                    let mut view = world.write_resource::<View>();
+                    view.flipped = !view.flipped;
+                }
+                "undo" if !networked => self.step(world, false),
+                "redo" if !networked => self.step(world, true),
+                "resign" if networked => world.write_resource::<Network>().resign(),
+                "offer_draw" if networked => world.write_resource::<Network>().offer_draw(),
+                "replay_back" if self.mode == Mode::Replay => self.step(world, false),
+                "replay_forward" if self.mode == Mode::Replay => self.step(world, true),
+                _ => {}
+            }
+        }
         if let StateEvent::Window(event) = &event {
             // Check if the window should be closed
-            if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+            if is_close_requested(&event) {
                 return Trans::Quit;
             }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Push(Box::new(PauseState::default()));
+            }
+            if chatting && is_key_down(&event, VirtualKeyCode::Return) {
+                send_chat(world);
+            }
 
             // Listen to any key events
             if let Some(event) = get_key(&event) {
@@ -65,9 +204,92 @@ impl SimpleState for MyState {
         // Keep going
         Trans::None
     }
+
+    /// Coming back from the game over screen by undoing the last move.
+    /// The pause screen leaves the board as it was.
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if std::mem::take(&mut self.resync) {
+            self.sync_pieces(data.world);
+        }
+    }
+
+    /// Ends the game as soon as a flag falls, or the side to move is mated
+    /// or stalemated and the last move has been animated.
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(dispatcher) = self.dispatcher.as_mut() {
+            dispatcher.dispatch(data.world);
+        }
+        if self.mode == Mode::Replay {
+            return Trans::None;
+        }
+        match ended(data.world) {
+            Some(status) => {
+                self.resync = true;
+                Trans::Push(Box::new(GameOverState::new(status)))
+            }
+            None => Trans::None,
+        }
+    }
 }
 
 impl MyState {
+    /// The board takes the size of the one `game` starts on, the squares
+    /// are sized to fit it onto the screen on start.
+    pub fn new(game: Game, mode: Mode, config: GameConfig) -> MyState {
+        let board = game.start().board();
+        MyState {
+            grid_size: (board.width(), board.height()),
+            sprite_size: 100.,
+            game,
+            mode,
+            config,
+            dispatcher: None,
+            connection: None,
+            remaining: None,
+            entities: vec![],
+            resync: false,
+        }
+    }
+
+    /// Starts the clocks with `remaining` time for white and black instead
+    /// of the full time.
+    pub fn with_remaining(mut self, remaining: Option<(Duration, Duration)>) -> MyState {
+        self.remaining = remaining;
+        self
+    }
+
+    /// Plays against the other end of `connection` instead of on this machine only.
+    pub fn with_connection(mut self, connection: Connection) -> MyState {
+        self.connection = Some(connection);
+        self
+    }
+
+    /// Builds the dispatcher running the systems of the game, once all the
+    /// resources they expect are in the `world`.
+    fn init_dispatcher(&mut self, world: &mut World) {
+        let pool = (*world.read_resource::<ArcThreadPool>()).clone();
+        let mut builder = DispatcherBuilder::new()
+            .with_pool(pool)
+            .with(MoveSystem::default(), "king_movement_system", &[])
+            .with(TurnSystem, "turn_system", &["king_movement_system"])
+            .with(TweenSystem, "tween_system", &["king_movement_system"])
+            .with(ClockSystem::default(), "clock_system", &["king_movement_system"])
+            .with(AiSystem::default(), "ai_system", &["king_movement_system"])
+            .with(
+                ColorPieceSystem::default(),
+                "color_piece_system",
+                &["king_movement_system"],
+            )
+            .with(ViewSystem::default(), "view_system", &["king_movement_system"])
+            .with(BadgeSystem, "badge_system", &["king_movement_system"]);
+        if let Some(connection) = self.connection.take() {
+            builder.add(NetSystem::new(connection), "net_system", &["king_movement_system"]);
+        }
+        let mut dispatcher = builder.build();
+        dispatcher.setup(world);
+        self.dispatcher = Some(dispatcher);
+    }
+
     /// Loads and splits the `logo.png` image asset into 3 sprites,
     /// which will then be assigned to entities for rendering them.
     ///
@@ -108,9 +330,9 @@ impl MyState {
         sheet_handle
     }
 
-    /// Creates an entity in the `world` for each of the provided `sprites`.
-    /// They are individually placed around the center of the screen.
-    fn init_background_sprites(&self, world: &mut World, spritesheethandle: Handle<SpriteSheet>) {
+    /// Creates an entity in the `world` for each square of the board, dark
+    /// in the corner of `Player::FIRST` on the left as on a real board.
+    fn init_background_sprites(&mut self, world: &mut World, spritesheethandle: Handle<SpriteSheet>) {
         //----loading grid -----
         let (grid_x, grid_y) = self.grid_size;
         // size of sprites in jpg TODO: find a way to extract it directly
@@ -119,97 +341,194 @@ impl MyState {
         let sprite_render_white = SpriteRender::new(spritesheethandle.clone(), 0);
         let sprite_render_black = SpriteRender::new(spritesheethandle.clone(), 1);
 
-        for x in 0..grid_x / 2 {
+        for x in 0..grid_x {
             for y in 0..grid_y {
+                let sprite_render = if (x + y) % 2 == 1 {
+                    sprite_render_white.clone()
+                } else {
+                    sprite_render_black.clone()
+                };
                 let mut transform = Transform::default();
-                let sgn = ((y + 1) % 2) as f32;
                 transform.set_scale(Vector3::new(scale, scale, 1.));
-                transform.set_translation_xyz(
-                    x as f32 * (2. * self.sprite_size) + self.sprite_size * sgn,
-                    y as f32 * self.sprite_size,
-                    0.,
-                );
                 //screen centers the sprite on position
-                transform.prepend_translation(Vector3::new(
-                    self.sprite_size / 2.,
-                    self.sprite_size / 2.,
-                    0.,
-                ));
-                world
-                    .create_entity()
-                    .with(sprite_render_white.clone())
-                    .with(transform)
-                    .build();
-                let sgn = (y % 2) as f32;
-
-                let mut transform = Transform::default();
                 transform.set_translation_xyz(
-                    x as f32 * (2. * self.sprite_size) + self.sprite_size * sgn,
-                    y as f32 * self.sprite_size,
+                    x as f32 * self.sprite_size + self.sprite_size / 2.,
+                    y as f32 * self.sprite_size + self.sprite_size / 2.,
                     0.,
                 );
-                transform.prepend_translation(Vector3::new(
-                    self.sprite_size / 2.,
-                    self.sprite_size / 2.,
-                    0.,
-                ));
-                transform.set_scale(Vector3::new(scale, scale, 1.));
-                world
+                let square = world
                     .create_entity()
-                    .with(sprite_render_black.clone())
+                    .with(sprite_render)
                     .with(transform)
                     .build();
+                self.entities.push(square);
             }
         }
-        //-----loading pieces-------
     }
 
     fn init_piece_sprites(&self, world: &mut World, spritesheethandle: Handle<SpriteSheet>) {
+        world.insert(PieceSprites {
+            sheet: spritesheethandle,
+        });
+        world.insert(self.game.clone());
+        self.sync_pieces(world);
+    }
+
+    /// Replaces all piece entities and the `Grid` with ones matching the current
+    /// position of the `Game`, for when the game jumps instead of playing a move.
+    fn sync_pieces(&self, world: &mut World) {
         let def_spritesize = 60.;
         let scale = self.sprite_size as f32 / def_spritesize;
-        let (p1,p2) = (Player::FIRST,Player::SECOND);
-        //Rook
-        let sprite_render_figure_rook = SpriteRender::new(spritesheethandle.clone(), 2);
-        self.init_piece(sprite_render_figure_rook.clone(), world, &p1,(0,0), scale, PieceType::ROOK);
-        self.init_piece(sprite_render_figure_rook, world, &p1,(7,0), scale, PieceType::ROOK);
-        let sprite_render_figure_rook = SpriteRender::new(spritesheethandle.clone(), 8);
-        self.init_piece(sprite_render_figure_rook.clone(), world, &p2,(0,7), scale, PieceType::ROOK);
-        self.init_piece(sprite_render_figure_rook, world, &p2,(7,7), scale, PieceType::ROOK);
-        //Knight
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 3);
-        self.init_piece(sprite_render_figure.clone(), world, &p1,(1,0), scale, PieceType::KNIGHT);
-        self.init_piece(sprite_render_figure, world, &p1,(6,0), scale, PieceType::KNIGHT);
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 9);
-        self.init_piece(sprite_render_figure.clone(), world, &p2,(1,7), scale, PieceType::KNIGHT);
-        self.init_piece(sprite_render_figure, world, &p2,(6,7), scale,PieceType::KNIGHT);
-        //bishop
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 4);
-        self.init_piece(sprite_render_figure.clone(), world, &p1,(2,0), scale, PieceType::BISHOP);
-        self.init_piece(sprite_render_figure, world, &p1,(5,0), scale, PieceType::BISHOP);
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 10);
-        self.init_piece(sprite_render_figure.clone(), world, &p2,(2,7), scale, PieceType::BISHOP);
-        self.init_piece(sprite_render_figure, world, &p2,(5,7), scale, PieceType::BISHOP);
-        //Queen
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 0);
-        self.init_piece(sprite_render_figure, world, &p1,(4,0), scale, PieceType::QUEEN);
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 6);
-        self.init_piece(sprite_render_figure, world, &p2,(4,7), scale, PieceType::QUEEN);
-        //King
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 1);
-        self.init_piece(sprite_render_figure, world, &p1,(3,0), scale, PieceType::KING);
-        let sprite_render_figure = SpriteRender::new(spritesheethandle.clone(), 7);
-        self.init_piece(sprite_render_figure, world, &p2,(3,7), scale, PieceType::KING);
-        //Pawn
-        let sprite_render_figure1 = SpriteRender::new(spritesheethandle.clone(), 5);
-        let sprite_render_figure2 = SpriteRender::new(spritesheethandle.clone(), 11);
-        for i in 0..8 {
-        self.init_piece(sprite_render_figure1.clone(), world, &p1,(i,1), scale, PieceType::PAWN);
-        self.init_piece(sprite_render_figure2.clone(), world, &p2,(i,6), scale, PieceType::PAWN);
-        }
-        
-    }
-    fn init_piece(&self, sprite_render_piece: SpriteRender, world: &mut World, player: &Player, pos: (u32,u32), scale: f32, piece_type: PieceType) {
-            let piece = Piece::new(pos.0, pos.1, self.sprite_size, player.clone(), piece_type);
+        let old: Vec<Entity> = (&world.entities(), &world.read_storage::<Piece>())
+            .join()
+            .map(|(entity, _)| entity)
+            .collect();
+        world
+            .delete_entities(&old)
+            .expect("failed to delete pieces");
+
+        world.write_resource::<Grid>().clear();
+
+        let spritesheethandle = world.read_resource::<PieceSprites>().sheet.clone();
+        let game = world.read_resource::<Game>().clone();
+        let position = game.position();
+        for (pos, piece) in position.board().pieces() {
+            let sprite_render_piece = SpriteRender::new(spritesheethandle.clone(), sprite_index(piece));
+            let entity = self.init_piece(sprite_render_piece, world, &piece.player, pos, scale, piece.piece_type);
+            world.write_resource::<Grid>().place(pos, entity, piece.player);
+        }
+        world.insert(Turn {
+            player: position.side_to_move(),
+        });
+        world.insert(Captures {
+            pieces: game.captures(),
+        });
+    }
+
+    /// Goes one move back or forward in the game and puts the pieces,
+    /// including captured ones, where they stood at that point. The pieces
+    /// of the move slide from where they were. When playing, the moves of a
+    /// computer against a human are stepped over, or it would move again
+    /// at once; only the last move stepped slides.
+    fn step(&self, world: &mut World, forward: bool) {
+        if animating(world) {
+            return;
+        }
+        let controllers = *world.read_resource::<Controllers>();
+        // the move between the two positions and the position it was played in
+        let stepped = {
+            let mut game = world.write_resource::<Game>();
+            let mut stepped = None;
+            loop {
+                let step = if forward {
+                    let before = game.position().clone();
+                    if game.forward() {
+                        world.write_resource::<Redone>().0 = true;
+                        game.last_move().map(|mv| (mv, before))
+                    } else {
+                        None
+                    }
+                } else if game.back() {
+                    Some((game.moves()[game.ply()], game.position().clone()))
+                } else {
+                    None
+                };
+                match step {
+                    Some(step) => stepped = Some(step),
+                    None => break,
+                }
+                if self.mode != Mode::Play || !controllers.steps_over(game.position().side_to_move()) {
+                    break;
+                }
+            }
+            stepped
+        };
+        let (mv, before) = match stepped {
+            Some(stepped) => stepped,
+            None => return,
+        };
+        self.sync_pieces(world);
+
+        let mut slides = vec![(mv.from, mv.to)];
+        if let Some(rook) = before.castling_rook(mv) {
+            slides.push((rook.from, rook.to));
+        }
+        let animation = *world.read_resource::<AnimationConfig>();
+        let sliding: Vec<_> = slides
+            .into_iter()
+            .map(|(from, to)| if forward { (from, to) } else { (to, from) })
+            .filter_map(|(from, to)| world.read_resource::<Grid>().entity(to).map(|entity| (entity, from)))
+            .collect();
+        for (entity, from) in sliding {
+            let to = match world.read_storage::<Transform>().get(entity) {
+                Some(transform) => *transform.translation(),
+                None => continue,
+            };
+            let mut start = to;
+            start.x = from.0 as f32 * self.sprite_size + self.sprite_size / 2.;
+            start.y = from.1 as f32 * self.sprite_size + self.sprite_size / 2.;
+            world
+                .write_storage::<Tween>()
+                .insert(entity, animation.tween(start, to))
+                .expect("failed to animate piece");
+        }
+    }
+
+    /// Writes the game with the player names and today's date to the PGN file from the config.
+    fn save_pgn(&self, world: &mut World) {
+        let mut pgn = Pgn::new(world.read_resource::<Game>().clone());
+        pgn.set_tag("Event", "boardgame");
+        pgn.set_tag("Date", &Local::now().format("%Y.%m.%d").to_string());
+        pgn.set_tag("White", &self.config.white);
+        pgn.set_tag("Black", &self.config.black);
+        if let Some(player) = flagged(world) {
+            pgn.set_tag("Result", GameStatus::TimeOut(player.opponent()).result());
+            pgn.set_tag("Termination", "time forfeit");
+        }
+        if let Some(status) = world.try_fetch::<Network>().and_then(|network| network.ended) {
+            pgn.set_tag("Result", status.result());
+        }
+        write_to_root(&self.config.pgn_path, &pgn.to_string(), "game");
+    }
+
+    /// Writes the current position to the FEN file from the config.
+    fn export_fen(&self, world: &World) {
+        let fen = world.read_resource::<Game>().position().to_fen();
+        write_to_root(&self.config.fen_path, &format!("{}\n", fen), "position");
+    }
+    /// Writes an unfinished game to the save file from the config so it can
+    /// be continued from the menu, and removes the file once the game is over.
+    /// Replays and games over the network are not saved.
+    fn save_game(&self, world: &World) {
+        if self.mode != Mode::Play || world.has_value::<Network>() {
+            return;
+        }
+        let path = match application_root_dir() {
+            Ok(root) => root.join(&self.config.save_path),
+            Err(err) => {
+                error!("cannot find the application root: {}", err);
+                return;
+            }
+        };
+        let game = world.read_resource::<Game>();
+        if game.position().status() != GameStatus::Ongoing || flagged(world).is_some() {
+            if path.exists() {
+                if let Err(err) = fs::remove_file(&path) {
+                    error!("failed to remove {}: {}", path.display(), err);
+                }
+            }
+            return;
+        }
+        let clock = world.try_fetch::<Clock>();
+        let saved = SavedGame::new(&game, &self.config, clock.as_deref());
+        match saved.write(&path) {
+            Ok(()) => info!("saved game to {}", path.display()),
+            Err(err) => error!("failed to save game to {}: {}", path.display(), err),
+        }
+    }
+
+    fn init_piece(&self, sprite_render_piece: SpriteRender, world: &mut World, player: &Player, pos: (u32,u32), scale: f32, piece_type: PieceType) -> Entity {
+            let piece = Piece::new(pos.0, pos.1, self.sprite_size, *player, piece_type);
 
             let mut transform = Transform::default();
             transform.set_translation(piece.get_self_xy());
@@ -220,21 +539,169 @@ impl MyState {
                 .with(sprite_render_piece)
                 .with(Status::None)
                 .with(transform)
-                .build();
+                .build()
+    }
+
+    /// Creates the ui text showing whose turn it is and the prompt below it,
+    /// kept up to date by the `TurnSystem`.
+    fn init_turn_text(&mut self, world: &mut World) {
+        let text = create_text(world, "turn", Anchor::TopLeft, 10., -10.);
+        let prompt = create_text(world, "prompt", Anchor::TopLeft, 10., -50.);
+        self.entities.extend(&[text, prompt]);
+        world.insert(TurnText { text, prompt });
+    }
+
+    /// Creates the chat of a game over the network: a line to click into and
+    /// type, and above it the last line sent or received. Spectators only
+    /// read what the players write.
+    fn init_chat(&mut self, world: &mut World) {
+        let last = create_text(world, "chat_last", Anchor::BottomLeft, 10., 50.);
+        let input = create_text(world, "chat_input", Anchor::BottomLeft, 10., 10.);
+        self.entities.extend(&[last, input]);
+        world.insert(ChatText { input, last });
+        if self.mode == Mode::Watch {
+            return;
+        }
+        if let Some(text) = world.write_storage::<UiText>().get_mut(last) {
+            text.text = "Click below to chat, enter sends".to_string();
+        }
+        world
+            .write_storage::<TextEditing>()
+            .insert(input, TextEditing::new(100, [0., 0., 0., 1.], [1., 1., 1., 1.], false))
+            .expect("failed to make chat editable");
+        world
+            .write_storage::<Interactable>()
+            .insert(input, Interactable)
+            .expect("failed to make chat clickable");
+        world
+            .write_storage::<Selectable<()>>()
+            .insert(input, Selectable::<()>::new(0))
+            .expect("failed to make chat selectable");
+    }
+
+    /// Starts the clocks if a time control is selected in the config. The
+    /// times are shown on the side of each player, kept up to date by the `ClockSystem`.
+    fn init_clocks(&mut self, world: &mut World) {
+        let control = match self.config.clock.time_control() {
+            Some(control) => control,
+            None => return,
+        };
+        let mut clock = Clock::new(control);
+        if let Some((first, second)) = self.remaining {
+            clock.set_remaining(Player::FIRST, first);
+            clock.set_remaining(Player::SECOND, second);
+        }
+        world.insert(clock);
+        let second = create_text(world, "clock_second", Anchor::TopRight, -10., -10.);
+        let first = create_text(world, "clock_first", Anchor::BottomRight, -10., 10.);
+        self.entities.extend(&[first, second]);
+        world.insert(ClockText { first, second });
     }
 
     /// Creates a camera entity in the `world`.
     ///
-    /// The `dimensions` are used to center the camera in the middle
-    /// of the screen, as well as make it cover the entire screen.
-    fn init_camera(world: &mut World, dimensions: &ScreenDimensions) {
+    /// The camera looks at `center`, the middle of the board, and covers
+    /// the entire screen given by the `dimensions`.
+    fn init_camera(world: &mut World, dimensions: &ScreenDimensions, center: (f32, f32)) -> Entity {
         let mut transform = Transform::default();
-        transform.set_translation_xyz(dimensions.width() / 2., dimensions.height() / 2., 200.0);
+        transform.set_translation_xyz(center.0, center.1, 200.0);
 
         world
             .create_entity()
             .with(Camera::standard_2d(dimensions.width(), dimensions.height()))
             .with(transform)
-            .build();
+            .build()
+    }
+}
+
+/// How the game ended, if it did: a flag fell, a player resigned or
+/// agreed to a draw, or the last move, once animated, ended it.
+fn ended(world: &World) -> Option<GameStatus> {
+    if let Some(player) = flagged(world) {
+        return Some(GameStatus::TimeOut(player.opponent()));
+    }
+    if let Some(status) = world.try_fetch::<Network>().and_then(|network| network.ended) {
+        return Some(status);
+    }
+    if animating(world) {
+        return None;
+    }
+    match world.read_resource::<Outcome>().status {
+        GameStatus::Ongoing => None,
+        status => Some(status),
+    }
+}
+
+/// The player whose time ran out, if playing with clocks.
+fn flagged(world: &World) -> Option<Player> {
+    world.try_fetch::<Clock>()?.flagged()
+}
+
+/// Hands the typed chat line to the `NetSystem` and empties the input.
+fn send_chat(world: &mut World) {
+    let input = world.read_resource::<ChatText>().input;
+    let line = match world.write_storage::<UiText>().get_mut(input) {
+        Some(text) => std::mem::take(&mut text.text),
+        None => return,
+    };
+    if !line.trim().is_empty() {
+        world.write_resource::<Network>().outbox.push(Message::Chat(line));
+    }
+}
+
+/// Writes `text` to `path` below the application root, logging how it went.
+/// `what` names the content in the log, e.g. `game`.
+fn write_to_root(path: &str, text: &str, what: &str) {
+    let path = match application_root_dir() {
+        Ok(root) => root.join(path),
+        Err(err) => {
+            error!("cannot find the application root: {}", err);
+            return;
+        }
+    };
+    match fs::write(&path, text) {
+        Ok(()) => info!("saved {} to {}", what, path.display()),
+        Err(err) => error!("failed to save {} to {}: {}", what, path.display(), err),
     }
 }
+
+/// All entities with a `T`, e.g. the pieces.
+fn entities_with<T: Component>(world: &World) -> Vec<Entity> {
+    (&world.entities(), &world.read_storage::<T>())
+        .join()
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Returns true while pieces are still sliding to their squares.
+fn animating(world: &World) -> bool {
+    (&world.read_storage::<Tween>()).join().next().is_some()
+}
+
+/// A single line of white ui text in the Bangers font, placed at `anchor`
+/// and moved in by `x` and `y`.
+fn create_text(world: &mut World, id: &str, anchor: Anchor, x: f32, y: f32) -> Entity {
+    let font = world.read_resource::<Loader>().load(
+        "fonts/Bangers-Regular.ttf",
+        TtfFormat,
+        (),
+        &world.read_resource(),
+    );
+    let transform = UiTransform::new(id.to_string(), anchor, anchor, x, y, 5., 300., 40.);
+    let align = match anchor {
+        Anchor::TopRight | Anchor::BottomRight => Anchor::MiddleRight,
+        _ => Anchor::MiddleLeft,
+    };
+    world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font,
+            String::new(),
+            [1., 1., 1., 1.],
+            30.,
+            LineMode::Single,
+            align,
+        ))
+        .build()
+}
@@ -0,0 +1,122 @@
+use amethyst::ecs::{Entity, Read, ReadExpect, System, WriteStorage};
+use amethyst::ui::UiText;
+use chess::{Game, GameStatus, Move, Player};
+use serde::{Deserialize, Serialize};
+
+/// The player allowed to move next.
+pub struct Turn {
+    pub player: Player,
+}
+impl Default for Turn {
+    fn default() -> Self {
+        Turn {
+            player: Player::FIRST,
+        }
+    }
+}
+
+/// What the player has to do before their move is played, e.g. pick the
+/// piece a pawn promotes to, or why it was not.
+#[derive(Default)]
+pub struct Prompt {
+    pub text: Option<String>,
+}
+
+/// How the game stands, worked out by the `MoveSystem` only when a move
+/// was played or taken back, rather than by every system in every frame.
+pub struct Outcome {
+    pub status: GameStatus,
+    /// Ply and last move of the game the status belongs to.
+    at: Option<(usize, Option<Move>)>,
+}
+impl Outcome {
+    /// Works the status out again if `game` moved since the last time.
+    pub fn update(&mut self, game: &Game) {
+        let at = (game.ply(), game.last_move());
+        if self.at != Some(at) {
+            self.status = game.position().status();
+            self.at = Some(at);
+        }
+    }
+}
+impl Default for Outcome {
+    fn default() -> Self {
+        Outcome {
+            status: GameStatus::Ongoing,
+            at: None,
+        }
+    }
+}
+
+/// Who makes the moves of a player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Controller {
+    /// Moves with the mouse.
+    Human,
+    /// The built-in search, see `AiSystem`.
+    Ai,
+    /// An external engine speaking UCI, set up in the `engine` config.
+    Uci,
+    /// The other player of a game over the network, see `NetSystem`.
+    Remote,
+}
+
+/// The controllers of both players.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Controllers {
+    pub first: Controller,
+    pub second: Controller,
+}
+impl Controllers {
+    pub fn get(&self, player: Player) -> Controller {
+        match player {
+            Player::SECOND => self.second,
+            _ => self.first,
+        }
+    }
+
+    /// Returns true if `player` is a computer playing a human. Taking back
+    /// moves skips the positions it is to move in, it would move again at once.
+    pub fn steps_over(&self, player: Player) -> bool {
+        let human = self.first == Controller::Human || self.second == Controller::Human;
+        human && self.get(player) != Controller::Human
+    }
+}
+impl Default for Controllers {
+    fn default() -> Self {
+        Controllers {
+            first: Controller::Human,
+            second: Controller::Human,
+        }
+    }
+}
+
+/// The ui entities showing whose turn it is and the `Prompt` below.
+pub struct TurnText {
+    pub text: Entity,
+    pub prompt: Entity,
+}
+
+/// Keeps the turn text and the prompt up to date.
+pub struct TurnSystem;
+
+impl<'s> System<'s> for TurnSystem {
+    type SystemData = (
+        ReadExpect<'s, Turn>,
+        ReadExpect<'s, TurnText>,
+        Read<'s, Prompt>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (turn, turn_text, prompt, mut ui_texts): Self::SystemData) {
+        if let Some(text) = ui_texts.get_mut(turn_text.text) {
+            text.text = match turn.player {
+                Player::FIRST => "White to move".to_string(),
+                _ => "Black to move".to_string(),
+            };
+        }
+        if let Some(text) = ui_texts.get_mut(turn_text.prompt) {
+            text.text = prompt.text.clone().unwrap_or_default();
+        }
+    }
+}
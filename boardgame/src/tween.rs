@@ -0,0 +1,144 @@
+use amethyst::core::{Time, Transform};
+use amethyst::ecs::{
+    Component, DenseVecStorage, Entities, Join, Read, System, WriteStorage,
+};
+use amethyst::renderer::{palette::Srgba, resources::Tint, transparent::Transparent};
+use nalgebra::base::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// How an animation speeds up and slows down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Easing {
+    Linear,
+    /// Starts fast and slows down towards the end.
+    EaseOut,
+    /// Speeds up, then slows down again.
+    EaseInOut,
+}
+impl Easing {
+    /// Maps the passed share of the time onto the covered share of the way.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+/// Length and easing of the piece animations, from `config/game.ron`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnimationConfig {
+    pub duration_ms: u64,
+    pub easing: Easing,
+}
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        AnimationConfig {
+            duration_ms: 250,
+            easing: Easing::EaseInOut,
+        }
+    }
+}
+impl AnimationConfig {
+    /// A tween from `from` to `to` with these settings.
+    pub fn tween(&self, from: Vector3<f32>, to: Vector3<f32>) -> Tween {
+        Tween {
+            from,
+            to,
+            elapsed: 0.,
+            duration: self.duration_ms as f32 / 1000.,
+            easing: self.easing,
+        }
+    }
+
+    pub fn fade(&self) -> Fade {
+        Fade {
+            elapsed: 0.,
+            duration: self.duration_ms as f32 / 1000.,
+        }
+    }
+}
+
+/// Moves the `Transform` of its entity from `from` to `to`, drawn above
+/// everything else on the way. Removed once there.
+pub struct Tween {
+    from: Vector3<f32>,
+    to: Vector3<f32>,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+impl Component for Tween {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Fades its entity out and deletes it, used for captured pieces.
+pub struct Fade {
+    elapsed: f32,
+    duration: f32,
+}
+impl Component for Fade {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Plays the `Tween`s and `Fade`s. While any of them runs the `MoveSystem`
+/// takes no input.
+pub struct TweenSystem;
+
+impl<'s> System<'s> for TweenSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Tween>,
+        WriteStorage<'s, Fade>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, Transparent>,
+        Read<'s, Time>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut tweens, mut fades, mut transforms, mut tints, mut transparent, time): Self::SystemData,
+    ) {
+        let delta = time.delta_seconds();
+        let mut finished = vec![];
+        for (entity, tween, transform) in (&entities, &mut tweens, &mut transforms).join() {
+            tween.elapsed += delta;
+            let t = (tween.elapsed / tween.duration).min(1.);
+            if t < 1. {
+                let mut at = tween.from.lerp(&tween.to, tween.easing.apply(t));
+                at.z = 2.;
+                transform.set_translation(at);
+            } else {
+                transform.set_translation(tween.to);
+                finished.push(entity);
+            }
+        }
+        for entity in finished {
+            tweens.remove(entity);
+        }
+
+        for (entity, fade) in (&entities, &mut fades).join() {
+            fade.elapsed += delta;
+            let alpha = 1. - (fade.elapsed / fade.duration).min(1.);
+            if alpha <= 0. {
+                entities.delete(entity).expect("failed to delete faded entity");
+                continue;
+            }
+            transparent
+                .insert(entity, Transparent)
+                .expect("failed to make fading entity transparent");
+            tints
+                .insert(entity, Tint(Srgba::new(1., 1., 1., alpha)))
+                .expect("failed to tint fading entity");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use chess::{PieceSet, PieceType, Position};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A chess variant as written in `config/game.ron`, e.g.
+/// `(layout: "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1", pieces: "KQRBNP", double_step: false)`
+/// for Gardner's 5x5 minichess.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VariantDefinition {
+    /// Starting position in FEN, which also gives the size of the board.
+    pub layout: String,
+    /// Letters of the piece types in play. Pawns promote to all of them
+    /// but the king and the pawn.
+    pub pieces: String,
+    /// Whether pawns may move two squares from their first row.
+    #[serde(default = "double_step")]
+    pub double_step: bool,
+    /// Squares the king moves when castling, e.g. 3 on the ten files of
+    /// Capablanca chess.
+    #[serde(default = "castling_distance")]
+    pub castling_distance: u32,
+}
+impl VariantDefinition {
+    /// Plays `position` by the rules of the variant. Fails if it holds
+    /// pieces the variant does not have.
+    fn apply_rules(&self, position: &mut Position) -> Result<(), String> {
+        let pieces = PieceSet::parse(&self.pieces)
+            .ok_or_else(|| format!("unknown piece in '{}'", self.pieces))?;
+        if let Some((_, piece)) = position
+            .board()
+            .pieces()
+            .find(|(_, piece)| !pieces.contains(piece.piece_type))
+        {
+            return Err(format!("{:?} is not one of the pieces {}", piece.piece_type, pieces));
+        }
+        let promotions: Vec<PieceType> = pieces
+            .iter()
+            .filter(|&t| t != PieceType::KING && t != PieceType::PAWN)
+            .collect();
+        position.set_promotions(PieceSet::of(&promotions));
+        position.set_double_step(self.double_step);
+        if self.castling_distance < 2 {
+            return Err(format!("castling_distance {} is below 2", self.castling_distance));
+        }
+        position.set_castling_distance(self.castling_distance);
+        Ok(())
+    }
+}
+
+fn double_step() -> bool {
+    true
+}
+
+pub fn castling_distance() -> u32 {
+    2
+}
+
+/// The variants to choose from and the chosen one.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct VariantConfig {
+    /// Name of the variant to play, standard chess if `None`.
+    pub selected: Option<String>,
+    pub variants: BTreeMap<String, VariantDefinition>,
+}
+impl VariantConfig {
+    /// The selected variant, `None` for standard chess.
+    fn definition(&self) -> Result<Option<&VariantDefinition>, String> {
+        match &self.selected {
+            Some(name) => match self.variants.get(name) {
+                Some(definition) => Ok(Some(definition)),
+                None => Err(format!("unknown variant {}", name)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// The starting position of the selected variant.
+    pub fn start(&self) -> Result<Position, String> {
+        match self.definition()? {
+            Some(definition) => self.position(&definition.layout),
+            None => Ok(Position::standard()),
+        }
+    }
+
+    /// The position `fen` played by the rules of the selected variant.
+    pub fn position(&self, fen: &str) -> Result<Position, String> {
+        let mut position = Position::from_fen(fen).map_err(|err| err.to_string())?;
+        if let Some(definition) = self.definition()? {
+            definition.apply_rules(&mut position)?;
+        }
+        Ok(position)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::clock::ClockText;
+use crate::piece::Piece;
+use crate::turn::{Controller, Controllers};
+use amethyst::core::Transform;
+use amethyst::ecs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use amethyst::renderer::Camera;
+use amethyst::ui::{Anchor, UiTransform};
+use amethyst::window::ScreenDimensions;
+use chess::{Game, Player};
+use std::f32::consts::PI;
+
+/// Which way round the board is shown. Everything is placed on the board
+/// as seen by `Player::FIRST`, flipping only turns the camera.
+#[derive(Default)]
+pub struct View {
+    /// `Player::SECOND` at the bottom.
+    pub flipped: bool,
+    /// Turn the board towards the side to move after every move, for two
+    /// people sharing one screen.
+    pub auto_flip: bool,
+    /// Point the camera looks at, the middle of the board.
+    pub center: (f32, f32),
+}
+impl View {
+    /// The point on the board under a point in the window, whose `y` goes
+    /// down from the top.
+    pub fn to_board(&self, (x, y): (f32, f32), dimensions: &ScreenDimensions) -> (f32, f32) {
+        // from the middle of the window, with y going up
+        let (dx, dy) = (x - dimensions.width() / 2., dimensions.height() / 2. - y);
+        if self.flipped {
+            (self.center.0 - dx, self.center.1 - dy)
+        } else {
+            (self.center.0 + dx, self.center.1 + dy)
+        }
+    }
+}
+
+/// Turns the camera by 180° while the `View` is flipped, and the pieces
+/// and clocks with it so they stay upright and next to their player.
+#[derive(Default)]
+pub struct ViewSystem {
+    /// Ply of the game in the last frame, to notice moves.
+    ply: Option<usize>,
+}
+
+impl<'s> System<'s> for ViewSystem {
+    type SystemData = (
+        Write<'s, View>,
+        ReadExpect<'s, Game>,
+        Read<'s, Controllers>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Piece>,
+        WriteStorage<'s, Transform>,
+        Option<ReadExpect<'s, ClockText>>,
+        WriteStorage<'s, UiTransform>,
+    );
+
+    fn run(
+        &mut self,
+        (mut view, game, controllers, cameras, pieces, mut transforms, clock_text, mut ui_transforms): Self::SystemData,
+    ) {
+        let ply = game.ply();
+        if view.auto_flip && self.ply != Some(ply) {
+            // only towards a player sitting in front of the screen
+            let side = game.position().side_to_move();
+            if controllers.get(side) == Controller::Human {
+                view.flipped = side == Player::SECOND;
+            }
+        }
+        self.ply = Some(ply);
+
+        let angle = if view.flipped { PI } else { 0. };
+        for (_, transform) in (&cameras, &mut transforms).join() {
+            transform.set_rotation_2d(angle);
+        }
+        for (_, transform) in (&pieces, &mut transforms).join() {
+            transform.set_rotation_2d(angle);
+        }
+
+        if let Some(clock_text) = clock_text {
+            let (bottom, top) = if view.flipped {
+                (clock_text.second, clock_text.first)
+            } else {
+                (clock_text.first, clock_text.second)
+            };
+            for &(text, anchor, y) in &[(bottom, Anchor::BottomRight, 10.), (top, Anchor::TopRight, -10.)] {
+                if let Some(ui_transform) = ui_transforms.get_mut(text) {
+                    ui_transform.anchor = anchor;
+                    ui_transform.pivot = anchor;
+                    ui_transform.local_y = y;
+                }
+            }
+        }
+    }
+}
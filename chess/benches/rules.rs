@@ -0,0 +1,59 @@
+//! Compares the general `Position` with the bitboard `BitPosition`.
+//! Run with `cargo bench`.
+use chess::{search, BitPosition, Limits, Position, Rules, START_FEN};
+use std::time::{Duration, Instant};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+/// Runs `f` at least once and for about a second, returns the fastest run.
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let start = Instant::now();
+    let mut best = Duration::MAX;
+    loop {
+        let run = Instant::now();
+        let result = f();
+        best = best.min(run.elapsed());
+        if start.elapsed() > Duration::from_secs(1) {
+            return (best, result);
+        }
+    }
+}
+
+fn bench<T: PartialEq + std::fmt::Debug>(
+    name: &str,
+    naive: impl FnMut() -> T,
+    bits: impl FnMut() -> T,
+) {
+    let (naive_time, naive_result) = time(naive);
+    let (bits_time, bits_result) = time(bits);
+    assert_eq!(naive_result, bits_result, "{} differs", name);
+    println!(
+        "{:<24} naive {:>10.3?}  bitboard {:>10.3?}  {:>5.1}x",
+        name,
+        naive_time,
+        bits_time,
+        naive_time.as_secs_f64() / bits_time.as_secs_f64()
+    );
+}
+
+fn main() {
+    for &(name, fen, depth) in &[
+        ("perft 4 start", START_FEN, 4),
+        ("perft 3 kiwipete", KIWIPETE, 3),
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        let bits = BitPosition::from_position(&position).unwrap();
+        bench(name, || position.perft(depth), || bits.perft(depth));
+    }
+    let limits = Limits {
+        depth: 4,
+        time: None,
+    };
+    let position = Position::from_fen(KIWIPETE).unwrap();
+    let bits = BitPosition::from_position(&position).unwrap();
+    bench(
+        "search 4 kiwipete",
+        || search(&position, &limits).best_move,
+        || search(&bits, &limits).best_move,
+    );
+}
@@ -0,0 +1,485 @@
+//! Standard 8x8 chess on bitboards: one `u64` per player and piece type,
+//! bit `8 * y + x` standing for the square `(x, y)`.
+//!
+//! Jumping pieces look their attacks up in tables, sliding pieces follow
+//! precomputed rays up to the first blocker. All tables are built at
+//! compile time.
+use crate::board::Board;
+use crate::eval;
+use crate::movegen::{BISHOP_DIRECTIONS, KNIGHT_JUMPS, PROMOTIONS, ROOK_DIRECTIONS};
+use crate::piece::{Piece, PieceType};
+use crate::player::Player;
+use crate::position::{CastlingRights, Move, Position};
+use crate::rules::Rules;
+
+const KING_STEPS: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+/// Slot of each piece type in `BitPosition::pieces`.
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::KING,
+    PieceType::QUEEN,
+    PieceType::ROOK,
+    PieceType::KNIGHT,
+    PieceType::BISHOP,
+    PieceType::PAWN,
+];
+
+const KNIGHT_ATTACKS: [u64; 64] = step_table(&KNIGHT_JUMPS);
+const KING_ATTACKS: [u64; 64] = step_table(&KING_STEPS);
+/// Squares a pawn of either player attacks, indexed by `Player::index`.
+const PAWN_ATTACKS: [[u64; 64]; 2] = [
+    step_table(&[(-1, 1), (1, 1)]),
+    step_table(&[(-1, -1), (1, -1)]),
+];
+const ROOK_RAYS: [[u64; 64]; 4] = ray_tables(&ROOK_DIRECTIONS);
+const BISHOP_RAYS: [[u64; 64]; 4] = ray_tables(&BISHOP_DIRECTIONS);
+
+/// Squares reached from each square by one of the `deltas`.
+const fn step_table(deltas: &[(i32, i32)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as i32, (square / 8) as i32);
+        let mut i = 0;
+        while i < deltas.len() {
+            let (tx, ty) = (x + deltas[i].0, y + deltas[i].1);
+            if tx >= 0 && tx < 8 && ty >= 0 && ty < 8 {
+                table[square] |= 1 << (ty * 8 + tx);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+/// For each direction and square, the squares up to the edge of the board.
+const fn ray_tables(directions: &[(i32, i32); 4]) -> [[u64; 64]; 4] {
+    let mut tables = [[0; 64]; 4];
+    let mut d = 0;
+    while d < 4 {
+        let (dx, dy) = directions[d];
+        let mut square = 0;
+        while square < 64 {
+            let (mut x, mut y) = ((square % 8) as i32 + dx, (square / 8) as i32 + dy);
+            while x >= 0 && x < 8 && y >= 0 && y < 8 {
+                tables[d][square] |= 1 << (y * 8 + x);
+                x += dx;
+                y += dy;
+            }
+            square += 1;
+        }
+        d += 1;
+    }
+    tables
+}
+
+/// Squares attacked along the `rays` with `occupied` blocking the way.
+/// The first blocker of a ray is attacked, the squares behind it are not.
+fn slider_attacks(
+    rays: &[[u64; 64]; 4],
+    directions: &[(i32, i32); 4],
+    square: usize,
+    occupied: u64,
+) -> u64 {
+    let mut attacks = 0;
+    for (ray, &(dx, dy)) in rays.iter().zip(directions) {
+        let blockers = ray[square] & occupied;
+        if blockers == 0 {
+            attacks |= ray[square];
+            continue;
+        }
+        // rays going up the board meet their lowest bit first
+        let first = if dy > 0 || (dy == 0 && dx > 0) {
+            blockers.trailing_zeros()
+        } else {
+            63 - blockers.leading_zeros()
+        };
+        attacks |= ray[square] ^ ray[first as usize];
+    }
+    attacks
+}
+
+fn rook_attacks(square: usize, occupied: u64) -> u64 {
+    slider_attacks(&ROOK_RAYS, &ROOK_DIRECTIONS, square, occupied)
+}
+
+fn bishop_attacks(square: usize, occupied: u64) -> u64 {
+    slider_attacks(&BISHOP_RAYS, &BISHOP_DIRECTIONS, square, occupied)
+}
+
+fn index(square: (u32, u32)) -> usize {
+    (square.1 * 8 + square.0) as usize
+}
+
+fn square(index: usize) -> (u32, u32) {
+    (index as u32 % 8, index as u32 / 8)
+}
+
+fn type_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::KING => 0,
+        PieceType::QUEEN => 1,
+        PieceType::ROOK => 2,
+        PieceType::KNIGHT => 3,
+        PieceType::BISHOP => 4,
+        PieceType::PAWN => 5,
+        PieceType::ARCHBISHOP | PieceType::CHANCELLOR => unreachable!(),
+    }
+}
+
+/// Indices of the set bits, lowest first.
+fn bits(mut board: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if board == 0 {
+            return None;
+        }
+        let index = board.trailing_zeros() as usize;
+        board &= board - 1;
+        Some(index)
+    })
+}
+
+/// A `Position` on a standard 8x8 board, much faster to generate moves in.
+/// Convert with `from_position` and `to_position`; moves are the same
+/// `Move`s the general position uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitPosition {
+    /// Pieces indexed by `Player::index` and slot in `PIECE_TYPES`.
+    pieces: [[u64; 6]; 2],
+    /// All pieces of each player.
+    occupied: [u64; 2],
+    side_to_move: Player,
+    castling: [CastlingRights; 2],
+    en_passant: Option<usize>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl BitPosition {
+    /// `None` unless the board is 8x8 and holds only the standard pieces,
+    /// played by the standard rules.
+    pub fn from_position(position: &Position) -> Option<BitPosition> {
+        let board = position.board();
+        if (board.width(), board.height()) != (8, 8) || !position.standard_rules() {
+            return None;
+        }
+        if board
+            .pieces()
+            .any(|(_, piece)| !PIECE_TYPES.contains(&piece.piece_type))
+        {
+            return None;
+        }
+        let mut bits = BitPosition {
+            pieces: [[0; 6]; 2],
+            occupied: [0; 2],
+            side_to_move: position.side_to_move(),
+            castling: [
+                position.castling(Player::FIRST),
+                position.castling(Player::SECOND),
+            ],
+            en_passant: position.en_passant().map(index),
+            halfmove_clock: position.halfmove_clock(),
+            fullmove_number: position.fullmove_number(),
+        };
+        for (square, piece) in board.pieces() {
+            bits.put(index(square), piece);
+        }
+        Some(bits)
+    }
+
+    pub fn to_position(&self) -> Position {
+        let mut board = Board::new(8, 8);
+        for (square, piece) in self.pieces() {
+            board.set(square, Some(piece));
+        }
+        let mut position = Position::new(board, self.side_to_move);
+        for &player in &[Player::FIRST, Player::SECOND] {
+            position.set_castling(player, self.castling[player.index()]);
+        }
+        position.set_en_passant(self.en_passant.map(square));
+        position.set_move_counters(self.halfmove_clock, self.fullmove_number);
+        position
+    }
+
+    /// Every piece with its square.
+    pub fn pieces(&self) -> impl Iterator<Item = ((u32, u32), Piece)> + '_ {
+        [Player::FIRST, Player::SECOND]
+            .iter()
+            .flat_map(move |&player| {
+                PIECE_TYPES
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(t, &piece_type)| {
+                        bits(self.pieces[player.index()][t])
+                            .map(move |i| (square(i), Piece::new(player, piece_type)))
+                    })
+            })
+    }
+
+    /// Returns true if any piece of `by` attacks `square`.
+    pub fn is_attacked(&self, square: (u32, u32), by: Player) -> bool {
+        self.attacked(index(square), by)
+    }
+
+    fn attacked(&self, square: usize, by: Player) -> bool {
+        let theirs = &self.pieces[by.index()];
+        let occupied = self.occupied[0] | self.occupied[1];
+        let queens = theirs[type_index(PieceType::QUEEN)];
+        PAWN_ATTACKS[by.opponent().index()][square] & theirs[type_index(PieceType::PAWN)] != 0
+            || KNIGHT_ATTACKS[square] & theirs[type_index(PieceType::KNIGHT)] != 0
+            || KING_ATTACKS[square] & theirs[type_index(PieceType::KING)] != 0
+            || rook_attacks(square, occupied) & (theirs[type_index(PieceType::ROOK)] | queens) != 0
+            || bishop_attacks(square, occupied) & (theirs[type_index(PieceType::BISHOP)] | queens)
+                != 0
+    }
+
+    fn get(&self, square: usize) -> Option<Piece> {
+        let bit = 1 << square;
+        for &player in &[Player::FIRST, Player::SECOND] {
+            if self.occupied[player.index()] & bit == 0 {
+                continue;
+            }
+            let t = self.pieces[player.index()]
+                .iter()
+                .position(|board| board & bit != 0)?;
+            return Some(Piece::new(player, PIECE_TYPES[t]));
+        }
+        None
+    }
+
+    fn put(&mut self, square: usize, piece: Piece) {
+        let p = piece.player.index();
+        self.pieces[p][type_index(piece.piece_type)] |= 1 << square;
+        self.occupied[p] |= 1 << square;
+    }
+
+    fn take(&mut self, square: usize) -> Option<Piece> {
+        let piece = self.get(square)?;
+        let p = piece.player.index();
+        self.pieces[p][type_index(piece.piece_type)] &= !(1 << square);
+        self.occupied[p] &= !(1 << square);
+        Some(piece)
+    }
+
+    /// Moves that follow the piece rules but may leave the own king in check.
+    fn pseudo_moves(&self, moves: &mut Vec<Move>) {
+        let side = self.side_to_move;
+        let own = self.occupied[side.index()];
+        let enemy = self.occupied[side.opponent().index()];
+        let occupied = own | enemy;
+        let pieces = &self.pieces[side.index()];
+        for (t, &piece_type) in PIECE_TYPES.iter().enumerate() {
+            for from in bits(pieces[t]) {
+                let targets = match piece_type {
+                    PieceType::PAWN => {
+                        self.pawn_moves(from, occupied, enemy, moves);
+                        continue;
+                    }
+                    PieceType::KNIGHT => KNIGHT_ATTACKS[from],
+                    PieceType::KING => KING_ATTACKS[from],
+                    PieceType::ROOK => rook_attacks(from, occupied),
+                    PieceType::BISHOP => bishop_attacks(from, occupied),
+                    PieceType::QUEEN => {
+                        rook_attacks(from, occupied) | bishop_attacks(from, occupied)
+                    }
+                    PieceType::ARCHBISHOP | PieceType::CHANCELLOR => unreachable!(),
+                };
+                for to in bits(targets & !own) {
+                    moves.push(Move::new(square(from), square(to)));
+                }
+            }
+        }
+        self.castling_moves(occupied, moves);
+    }
+
+    fn pawn_moves(&self, from: usize, occupied: u64, enemy: u64, moves: &mut Vec<Move>) {
+        let side = self.side_to_move;
+        let (step, start_rank): (i32, usize) = match side {
+            Player::SECOND => (-8, 6),
+            _ => (8, 1),
+        };
+        let one = from as i32 + step;
+        // a pawn on the far rank, only possible in a FEN, has nowhere to go
+        if !(0..64).contains(&one) {
+            return;
+        }
+        let one = one as usize;
+        if occupied & (1 << one) == 0 {
+            self.pawn_move(from, one, moves);
+            let two = (one as i32 + step) as usize;
+            if from / 8 == start_rank && occupied & (1 << two) == 0 {
+                moves.push(Move::new(square(from), square(two)));
+            }
+        }
+        let en_passant = self.en_passant.map_or(0, |square| 1 << square);
+        for to in bits(PAWN_ATTACKS[side.index()][from] & (enemy | en_passant)) {
+            self.pawn_move(from, to, moves);
+        }
+    }
+
+    /// Adds a pawn move, once per piece it can turn into on the last row.
+    fn pawn_move(&self, from: usize, to: usize, moves: &mut Vec<Move>) {
+        if to / 8 == 0 || to / 8 == 7 {
+            for &piece_type in &PROMOTIONS {
+                moves.push(Move::with_promotion(square(from), square(to), piece_type));
+            }
+        } else {
+            moves.push(Move::new(square(from), square(to)));
+        }
+    }
+
+    /// Castling as in `Position`: the king moves two squares towards an
+    /// unmoved rook over empty squares, not out of or through check.
+    fn castling_moves(&self, occupied: u64, moves: &mut Vec<Move>) {
+        let side = self.side_to_move;
+        let rights = self.castling[side.index()];
+        let rank = match side {
+            Player::SECOND => 7,
+            _ => 0,
+        };
+        let king = match bits(self.pieces[side.index()][type_index(PieceType::KING)]).next() {
+            Some(king) if king / 8 == rank => king,
+            _ => return,
+        };
+        let rooks = self.pieces[side.index()][type_index(PieceType::ROOK)];
+        let corners = [
+            (rights.king_side, rank * 8 + 7, 1),
+            (rights.queen_side, rank * 8, -1),
+        ];
+        let mut in_check = None;
+        for &(allowed, rook, dx) in &corners {
+            if !allowed || rooks & (1 << rook) == 0 {
+                continue;
+            }
+            let (low, high) = (king.min(rook) + 1, king.max(rook));
+            let between = (low..high).fold(0u64, |board, square| board | 1 << square);
+            let to = king as i32 + 2 * dx;
+            if occupied & between != 0 || to < (rank * 8) as i32 || to > (rank * 8 + 7) as i32 {
+                continue;
+            }
+            let enemy = side.opponent();
+            if *in_check.get_or_insert_with(|| self.attacked(king, enemy))
+                || self.attacked((king as i32 + dx) as usize, enemy)
+            {
+                continue;
+            }
+            moves.push(Move::new(square(king), square(to as usize)));
+        }
+    }
+
+    fn update_castling(&mut self, piece: Piece, from: usize, to: usize) {
+        if piece.piece_type == PieceType::KING {
+            self.castling[piece.player.index()] = CastlingRights::default();
+        }
+        for (p, &rank) in [0, 7].iter().enumerate() {
+            let rights = &mut self.castling[p];
+            for &square in &[from, to] {
+                if square == rank * 8 {
+                    rights.queen_side = false;
+                }
+                if square == rank * 8 + 7 {
+                    rights.king_side = false;
+                }
+            }
+        }
+    }
+}
+
+impl Rules for BitPosition {
+    fn side_to_move(&self) -> Player {
+        self.side_to_move
+    }
+
+    fn piece_at(&self, square: (u32, u32)) -> Option<Piece> {
+        self.get(index(square))
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(48);
+        self.pseudo_moves(&mut moves);
+        let side = self.side_to_move;
+        moves.retain(|&mv| {
+            let mut next = self.clone();
+            next.make_move(mv);
+            !next.in_check(side)
+        });
+        moves
+    }
+
+    fn make_move(&mut self, mv: Move) -> Option<Piece> {
+        let (from, to) = (index(mv.from), index(mv.to));
+        let piece = self.get(from)?;
+        let captured = match self.capture_square(mv) {
+            Some(captured) => self.take(index(captured)),
+            None => None,
+        };
+        if piece.piece_type == PieceType::KING && (from as i32 - to as i32).abs() == 2 {
+            let (rook_from, rook_to) = if to > from {
+                (from - from % 8 + 7, from + 1)
+            } else {
+                (from - from % 8, from - 1)
+            };
+            if let Some(rook) = self.take(rook_from) {
+                self.put(rook_to, rook);
+            }
+        }
+        self.take(from);
+        let placed = match mv.promotion {
+            Some(piece_type) => Piece::new(piece.player, piece_type),
+            None => piece,
+        };
+        self.put(to, placed);
+
+        self.en_passant = None;
+        if piece.piece_type == PieceType::PAWN && (from as i32 - to as i32).abs() == 16 {
+            self.en_passant = Some((from + to) / 2);
+        }
+        self.update_castling(piece, from, to);
+
+        if captured.is_some() || piece.piece_type == PieceType::PAWN {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Player::SECOND {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opponent();
+        captured
+    }
+
+    fn in_check(&self, player: Player) -> bool {
+        match bits(self.pieces[player.index()][type_index(PieceType::KING)]).next() {
+            Some(king) => self.attacked(king, player.opponent()),
+            None => false,
+        }
+    }
+
+    fn capture_square(&self, mv: Move) -> Option<(u32, u32)> {
+        let to = index(mv.to);
+        if (self.occupied[0] | self.occupied[1]) & (1 << to) != 0 {
+            return Some(mv.to);
+        }
+        let pawn = type_index(PieceType::PAWN);
+        let pawns = self.pieces[0][pawn] | self.pieces[1][pawn];
+        if pawns & (1 << index(mv.from)) != 0 && mv.from.0 != mv.to.0 && Some(to) == self.en_passant
+        {
+            Some((mv.to.0, mv.from.1))
+        } else {
+            None
+        }
+    }
+
+    fn evaluate(&self) -> i32 {
+        eval::evaluate_pieces(self.pieces(), (8, 8), self.side_to_move)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::piece::Piece;
+
+/// Placement of the pieces on a rectangular grid.
+///
+/// Squares are addressed as `(x, y)` with `(0, 0)` in the bottom left corner,
+/// the same way the sprites are laid out on screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    width: u32,
+    height: u32,
+    squares: Vec<Option<Piece>>,
+}
+
+impl Board {
+    /// Creates an empty board.
+    pub fn new(width: u32, height: u32) -> Board {
+        Board {
+            width,
+            height,
+            squares: vec![None; (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns true if `(x, y)` lies on the board. Takes signed coordinates
+    /// so callers can step off the edge without underflowing.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height
+    }
+
+    pub fn get(&self, pos: (u32, u32)) -> Option<Piece> {
+        self.squares[self.index(pos)]
+    }
+
+    pub fn set(&mut self, pos: (u32, u32), piece: Option<Piece>) {
+        let i = self.index(pos);
+        self.squares[i] = piece;
+    }
+
+    /// Iterates over all occupied squares.
+    pub fn pieces(&self) -> impl Iterator<Item = ((u32, u32), Piece)> + '_ {
+        let width = self.width;
+        self.squares
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, sq)| sq.map(|p| ((i as u32 % width, i as u32 / width), p)))
+    }
+
+    fn index(&self, (x, y): (u32, u32)) -> usize {
+        assert!(x < self.width && y < self.height, "square off the board");
+        (y * self.width + x) as usize
+    }
+}
@@ -0,0 +1,128 @@
+use crate::movegen::{BISHOP_DIRECTIONS, KNIGHT_JUMPS, ROOK_DIRECTIONS};
+use crate::piece::PieceType;
+use crate::player::Player;
+use crate::position::Position;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    /// The contained player has won.
+    Checkmate(Player),
+    Stalemate,
+    /// The contained player has won on time. Only a `Clock` tells, never the position.
+    TimeOut(Player),
+    /// The opponent of the contained player resigned. Only the players tell.
+    Resignation(Player),
+    /// Both players agreed to a draw.
+    DrawAgreed,
+}
+
+impl GameStatus {
+    /// The result as written in PGN: `1-0`, `0-1`, `1/2-1/2`, or `*` while
+    /// the game is still going on.
+    pub fn result(self) -> &'static str {
+        match self {
+            GameStatus::Checkmate(Player::FIRST)
+            | GameStatus::TimeOut(Player::FIRST)
+            | GameStatus::Resignation(Player::FIRST) => "1-0",
+            GameStatus::Checkmate(_) | GameStatus::TimeOut(_) | GameStatus::Resignation(_) => "0-1",
+            GameStatus::Stalemate | GameStatus::DrawAgreed => "1/2-1/2",
+            GameStatus::Ongoing => "*",
+        }
+    }
+}
+
+impl Position {
+    /// Returns true if any piece of `by` attacks `square`, whether or not
+    /// that piece could legally move there.
+    pub fn is_attacked(&self, square: (u32, u32), by: Player) -> bool {
+        let (x, y) = (square.0 as i32, square.1 as i32);
+        let owns = |dx: i32, dy: i32, types: &[PieceType]| {
+            self.board().contains(x + dx, y + dy)
+                && match self.board().get(((x + dx) as u32, (y + dy) as u32)) {
+                    Some(piece) => piece.player == by && types.contains(&piece.piece_type),
+                    None => false,
+                }
+        };
+
+        let pawn_dy = -self.forward(by);
+        if owns(-1, pawn_dy, &[PieceType::PAWN]) || owns(1, pawn_dy, &[PieceType::PAWN]) {
+            return true;
+        }
+        let jumpers = [
+            PieceType::KNIGHT,
+            PieceType::ARCHBISHOP,
+            PieceType::CHANCELLOR,
+        ];
+        if KNIGHT_JUMPS.iter().any(|&(dx, dy)| owns(dx, dy, &jumpers)) {
+            return true;
+        }
+        let sliders = [
+            (
+                &ROOK_DIRECTIONS,
+                [PieceType::ROOK, PieceType::QUEEN, PieceType::CHANCELLOR],
+            ),
+            (
+                &BISHOP_DIRECTIONS,
+                [PieceType::BISHOP, PieceType::QUEEN, PieceType::ARCHBISHOP],
+            ),
+        ];
+        for (directions, types) in sliders.iter() {
+            for &(dx, dy) in directions.iter() {
+                if owns(dx, dy, &[PieceType::KING]) {
+                    return true;
+                }
+                let (mut cx, mut cy) = (x + dx, y + dy);
+                while self.board().contains(cx, cy) {
+                    if let Some(piece) = self.board().get((cx as u32, cy as u32)) {
+                        if piece.player == by && types.contains(&piece.piece_type) {
+                            return true;
+                        }
+                        break;
+                    }
+                    cx += dx;
+                    cy += dy;
+                }
+            }
+        }
+        false
+    }
+
+    /// Squares attacked by `by`, indexed as `map[x][y]`.
+    pub fn attack_map(&self, by: Player) -> Vec<Vec<bool>> {
+        (0..self.board().width())
+            .map(|x| {
+                (0..self.board().height())
+                    .map(|y| self.is_attacked((x, y), by))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn king_square(&self, player: Player) -> Option<(u32, u32)> {
+        self.board()
+            .pieces()
+            .find(|(_, piece)| piece.player == player && piece.piece_type == PieceType::KING)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Returns true if the king of `player` is attacked.
+    pub fn in_check(&self, player: Player) -> bool {
+        match self.king_square(player) {
+            Some(king) => self.is_attacked(king, player.opponent()),
+            None => false,
+        }
+    }
+
+    /// Whether the side to move can still play on.
+    pub fn status(&self) -> GameStatus {
+        let side = self.side_to_move();
+        if !self.legal_moves().is_empty() {
+            GameStatus::Ongoing
+        } else if self.in_check(side) {
+            GameStatus::Checkmate(side.opponent())
+        } else {
+            GameStatus::Stalemate
+        }
+    }
+}
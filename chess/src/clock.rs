@@ -0,0 +1,112 @@
+//! Chess clocks, counting down the thinking time of both players.
+use crate::player::Player;
+use std::time::Duration;
+
+/// How time is given back for a move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Increment {
+    /// Added after every move, the 3 of 5+3.
+    Fischer(Duration),
+    /// The time used for a move is given back afterwards, up to this much.
+    Bronstein(Duration),
+    /// The clock only starts running after this much of every move.
+    Delay(Duration),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Time each player starts with.
+    pub initial: Duration,
+    pub increment: Increment,
+}
+
+impl TimeControl {
+    /// The usual `minutes + seconds` notation, e.g. `fischer(5, 3)` for 5+3.
+    pub fn fischer(minutes: u64, increment_seconds: u64) -> TimeControl {
+        TimeControl {
+            initial: Duration::from_secs(minutes * 60),
+            increment: Increment::Fischer(Duration::from_secs(increment_seconds)),
+        }
+    }
+}
+
+/// The clocks of both players. The frontend lets time pass with `tick` for
+/// the side to move and calls `press` when a move is made.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    /// Time spent on the current move.
+    used: Duration,
+    flagged: Option<Player>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Clock {
+        Clock {
+            control,
+            remaining: [control.initial; 2],
+            used: Duration::from_secs(0),
+            flagged: None,
+        }
+    }
+
+    pub fn time_control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn remaining(&self, player: Player) -> Duration {
+        self.remaining[player.index()]
+    }
+
+    /// Sets the remaining time of `player`, e.g. when resuming a game.
+    pub fn set_remaining(&mut self, player: Player, remaining: Duration) {
+        self.remaining[player.index()] = remaining;
+    }
+
+    /// The player whose time ran out, if any. The clock stops then.
+    pub fn flagged(&self) -> Option<Player> {
+        self.flagged
+    }
+
+    /// Lets `elapsed` pass on the clock of `player`, who is thinking.
+    pub fn tick(&mut self, player: Player, elapsed: Duration) {
+        if self.flagged.is_some() {
+            return;
+        }
+        let before = self.used;
+        self.used += elapsed;
+        let charged = match self.control.increment {
+            Increment::Delay(delay) => {
+                self.used.saturating_sub(delay) - before.saturating_sub(delay)
+            }
+            _ => elapsed,
+        };
+        let remaining = &mut self.remaining[player.index()];
+        *remaining = remaining.saturating_sub(charged);
+        if *remaining == Duration::from_secs(0) {
+            self.flagged = Some(player);
+        }
+    }
+
+    /// Starts the next move without ending the last one, when moves are
+    /// taken back or redone: no increment is given and the time used on
+    /// the move so far is forgotten rather than charged to the other player.
+    pub fn switch(&mut self) {
+        self.used = Duration::from_secs(0);
+    }
+
+    /// Ends the move of `player` and starts the next one.
+    pub fn press(&mut self, player: Player) {
+        if self.flagged.is_some() {
+            return;
+        }
+        let bonus = match self.control.increment {
+            Increment::Fischer(increment) => increment,
+            Increment::Bronstein(delay) => self.used.min(delay),
+            Increment::Delay(_) => Duration::from_secs(0),
+        };
+        self.remaining[player.index()] += bonus;
+        self.used = Duration::from_secs(0);
+    }
+}
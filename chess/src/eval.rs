@@ -0,0 +1,134 @@
+use crate::piece::{Piece, PieceType};
+use crate::player::Player;
+use crate::position::Position;
+
+/// Scores a position in centipawns from the side to move's point of view:
+/// material plus a bonus for where each piece stands.
+pub fn evaluate(position: &Position) -> i32 {
+    let board = position.board();
+    evaluate_pieces(
+        board.pieces(),
+        (board.width(), board.height()),
+        position.side_to_move(),
+    )
+}
+
+/// `evaluate` for any way of listing the pieces on a board of `size`.
+pub(crate) fn evaluate_pieces(
+    pieces: impl Iterator<Item = ((u32, u32), Piece)>,
+    size: (u32, u32),
+    side: Player,
+) -> i32 {
+    let (width, height) = size;
+    pieces
+        .map(|((x, y), piece)| {
+            // the tables are written for 8x8 from the first player's side,
+            // other boards are stretched onto them
+            let tx = (x * 8 / width) as usize;
+            let ty = (y * 8 / height) as usize;
+            let ty = match piece.player {
+                Player::SECOND => 7 - ty,
+                _ => ty,
+            };
+            let score = value(piece.piece_type) + square_table(piece.piece_type)[7 - ty][tx];
+            if piece.player == side {
+                score
+            } else {
+                -score
+            }
+        })
+        .sum()
+}
+
+/// Material value of a piece in centipawns.
+pub fn value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::PAWN => 100,
+        PieceType::KNIGHT => 320,
+        PieceType::BISHOP => 330,
+        PieceType::ROOK => 500,
+        PieceType::QUEEN => 900,
+        PieceType::ARCHBISHOP => 825,
+        PieceType::CHANCELLOR => 875,
+        PieceType::KING => 0,
+    }
+}
+
+// Piece-square tables, read like a diagram with the first player at the bottom.
+#[rustfmt::skip]
+const PAWN_TABLE: [[i32; 8]; 8] = [
+    [ 0,  0,  0,  0,  0,  0,  0,  0],
+    [50, 50, 50, 50, 50, 50, 50, 50],
+    [10, 10, 20, 30, 30, 20, 10, 10],
+    [ 5,  5, 10, 25, 25, 10,  5,  5],
+    [ 0,  0,  0, 20, 20,  0,  0,  0],
+    [ 5, -5,-10,  0,  0,-10, -5,  5],
+    [ 5, 10, 10,-20,-20, 10, 10,  5],
+    [ 0,  0,  0,  0,  0,  0,  0,  0],
+];
+#[rustfmt::skip]
+const KNIGHT_TABLE: [[i32; 8]; 8] = [
+    [-50,-40,-30,-30,-30,-30,-40,-50],
+    [-40,-20,  0,  0,  0,  0,-20,-40],
+    [-30,  0, 10, 15, 15, 10,  0,-30],
+    [-30,  5, 15, 20, 20, 15,  5,-30],
+    [-30,  0, 15, 20, 20, 15,  0,-30],
+    [-30,  5, 10, 15, 15, 10,  5,-30],
+    [-40,-20,  0,  5,  5,  0,-20,-40],
+    [-50,-40,-30,-30,-30,-30,-40,-50],
+];
+#[rustfmt::skip]
+const BISHOP_TABLE: [[i32; 8]; 8] = [
+    [-20,-10,-10,-10,-10,-10,-10,-20],
+    [-10,  0,  0,  0,  0,  0,  0,-10],
+    [-10,  0,  5, 10, 10,  5,  0,-10],
+    [-10,  5,  5, 10, 10,  5,  5,-10],
+    [-10,  0, 10, 10, 10, 10,  0,-10],
+    [-10, 10, 10, 10, 10, 10, 10,-10],
+    [-10,  5,  0,  0,  0,  0,  5,-10],
+    [-20,-10,-10,-10,-10,-10,-10,-20],
+];
+#[rustfmt::skip]
+const ROOK_TABLE: [[i32; 8]; 8] = [
+    [ 0,  0,  0,  0,  0,  0,  0,  0],
+    [ 5, 10, 10, 10, 10, 10, 10,  5],
+    [-5,  0,  0,  0,  0,  0,  0, -5],
+    [-5,  0,  0,  0,  0,  0,  0, -5],
+    [-5,  0,  0,  0,  0,  0,  0, -5],
+    [-5,  0,  0,  0,  0,  0,  0, -5],
+    [-5,  0,  0,  0,  0,  0,  0, -5],
+    [ 0,  0,  0,  5,  5,  0,  0,  0],
+];
+#[rustfmt::skip]
+const QUEEN_TABLE: [[i32; 8]; 8] = [
+    [-20,-10,-10, -5, -5,-10,-10,-20],
+    [-10,  0,  0,  0,  0,  0,  0,-10],
+    [-10,  0,  5,  5,  5,  5,  0,-10],
+    [ -5,  0,  5,  5,  5,  5,  0, -5],
+    [  0,  0,  5,  5,  5,  5,  0, -5],
+    [-10,  5,  5,  5,  5,  5,  0,-10],
+    [-10,  0,  5,  0,  0,  0,  0,-10],
+    [-20,-10,-10, -5, -5,-10,-10,-20],
+];
+#[rustfmt::skip]
+const KING_TABLE: [[i32; 8]; 8] = [
+    [-30,-40,-40,-50,-50,-40,-40,-30],
+    [-30,-40,-40,-50,-50,-40,-40,-30],
+    [-30,-40,-40,-50,-50,-40,-40,-30],
+    [-30,-40,-40,-50,-50,-40,-40,-30],
+    [-20,-30,-30,-40,-40,-30,-30,-20],
+    [-10,-20,-20,-20,-20,-20,-20,-10],
+    [ 20, 20,  0,  0,  0,  0, 20, 20],
+    [ 20, 30, 10,  0,  0, 10, 30, 20],
+];
+
+fn square_table(piece_type: PieceType) -> &'static [[i32; 8]; 8] {
+    match piece_type {
+        PieceType::PAWN => &PAWN_TABLE,
+        PieceType::KNIGHT => &KNIGHT_TABLE,
+        PieceType::BISHOP | PieceType::ARCHBISHOP => &BISHOP_TABLE,
+        PieceType::ROOK | PieceType::CHANCELLOR => &ROOK_TABLE,
+        PieceType::QUEEN => &QUEEN_TABLE,
+        PieceType::KING => &KING_TABLE,
+    }
+}
@@ -0,0 +1,205 @@
+use crate::board::Board;
+use crate::piece::Piece;
+use crate::player::Player;
+use crate::position::{CastlingRights, Position};
+use crate::square::{parse_square, square_name};
+use std::error::Error;
+use std::fmt;
+
+/// FEN of the usual starting position.
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Most rows and squares per row a FEN may have. FENs come from the
+/// network and the command line, a larger board is taken as garbage.
+pub const MAX_BOARD_SIZE: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// Fewer than the four required fields.
+    MissingField,
+    InvalidPiece(char),
+    /// The rows do not all have the same width.
+    UnevenRows,
+    /// More than `MAX_BOARD_SIZE` rows or squares in a row.
+    TooLarge,
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidNumber(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingField => write!(f, "FEN needs at least four fields"),
+            FenError::InvalidPiece(c) => write!(f, "unknown piece '{}' in FEN", c),
+            FenError::UnevenRows => write!(f, "FEN rows differ in width"),
+            FenError::TooLarge => write!(
+                f,
+                "FEN board is larger than {0}x{0} squares",
+                MAX_BOARD_SIZE
+            ),
+            FenError::InvalidSideToMove(s) => write!(f, "invalid side to move '{}' in FEN", s),
+            FenError::InvalidCastling(s) => write!(f, "invalid castling rights '{}' in FEN", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant square '{}' in FEN", s),
+            FenError::InvalidNumber(s) => write!(f, "invalid move counter '{}' in FEN", s),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl Position {
+    /// Reads a position in Forsyth-Edwards Notation. The move counters may
+    /// be left out. Rows wider or narrower than eight squares are accepted,
+    /// which is what variants on other board sizes need, up to
+    /// `MAX_BOARD_SIZE` squares either way.
+    pub fn from_fen(fen: &str) -> Result<Position, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(FenError::MissingField);
+        }
+
+        let mut rows = vec![];
+        for row in fields[0].split('/') {
+            let mut squares = vec![];
+            let mut empty = 0usize;
+            for c in row.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    empty = empty
+                        .checked_mul(10)
+                        .and_then(|empty| empty.checked_add(digit as usize))
+                        .filter(|&empty| squares.len() + empty <= MAX_BOARD_SIZE)
+                        .ok_or(FenError::TooLarge)?;
+                    continue;
+                }
+                squares.extend((0..empty).map(|_| None));
+                empty = 0;
+                squares.push(Some(Piece::from_char(c).ok_or(FenError::InvalidPiece(c))?));
+                if squares.len() > MAX_BOARD_SIZE {
+                    return Err(FenError::TooLarge);
+                }
+            }
+            squares.extend((0..empty).map(|_| None));
+            rows.push(squares);
+            if rows.len() > MAX_BOARD_SIZE {
+                return Err(FenError::TooLarge);
+            }
+        }
+        let width = rows[0].len();
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return Err(FenError::UnevenRows);
+        }
+        let mut board = Board::new(width as u32, rows.len() as u32);
+        // FEN starts with the row furthest away from the first player
+        for (y, row) in rows.iter().rev().enumerate() {
+            for (x, piece) in row.iter().enumerate() {
+                board.set((x as u32, y as u32), *piece);
+            }
+        }
+
+        let side_to_move = match fields[1] {
+            "w" => Player::FIRST,
+            "b" => Player::SECOND,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+        let mut position = Position::new(board, side_to_move);
+
+        let (mut first, mut second) = (CastlingRights::default(), CastlingRights::default());
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                match c {
+                    'K' => first.king_side = true,
+                    'Q' => first.queen_side = true,
+                    'k' => second.king_side = true,
+                    'q' => second.queen_side = true,
+                    _ => return Err(FenError::InvalidCastling(fields[2].to_string())),
+                }
+            }
+        }
+        position.set_castling(Player::FIRST, first);
+        position.set_castling(Player::SECOND, second);
+
+        if fields[3] != "-" {
+            match parse_square(fields[3]) {
+                Some(square) if position.board().contains(square.0 as i32, square.1 as i32) => {
+                    position.set_en_passant(Some(square))
+                }
+                _ => return Err(FenError::InvalidEnPassant(fields[3].to_string())),
+            }
+        }
+
+        let number = |field: Option<&&str>, default: u32| match field {
+            Some(s) => s
+                .parse()
+                .map_err(|_| FenError::InvalidNumber(s.to_string())),
+            None => Ok(default),
+        };
+        let halfmove_clock = number(fields.get(4), 0)?;
+        let fullmove_number = number(fields.get(5), 1)?;
+        position.set_move_counters(halfmove_clock, fullmove_number);
+        Ok(position)
+    }
+
+    /// Writes the position in Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let board = self.board();
+        let mut rows = vec![];
+        for y in (0..board.height()).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+            for x in 0..board.width() {
+                match board.get((x, y)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        row.push(piece.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            rows.push(row);
+        }
+
+        let side = match self.side_to_move() {
+            Player::SECOND => "b",
+            _ => "w",
+        };
+
+        let mut castling = String::new();
+        let (first, second) = (self.castling(Player::FIRST), self.castling(Player::SECOND));
+        for &(allowed, c) in &[
+            (first.king_side, 'K'),
+            (first.queen_side, 'Q'),
+            (second.king_side, 'k'),
+            (second.queen_side, 'q'),
+        ] {
+            if allowed {
+                castling.push(c);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant() {
+            Some(square) => square_name(square),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            rows.join("/"),
+            side,
+            castling,
+            en_passant,
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+}
@@ -0,0 +1,112 @@
+use crate::piece::Piece;
+use crate::position::{Move, Position};
+
+/// A game from its starting position on, with every move played.
+///
+/// Stepping `back` and `forward` keeps the moves, so a game can be
+/// replayed. Playing a new move from an earlier position drops the moves
+/// that followed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    /// `positions[i + 1]` is the position after `moves[i]`.
+    positions: Vec<Position>,
+    moves: Vec<Move>,
+    /// Index of the current position.
+    ply: usize,
+}
+
+impl Game {
+    pub fn new(start: Position) -> Game {
+        Game {
+            positions: vec![start],
+            moves: vec![],
+            ply: 0,
+        }
+    }
+
+    pub fn start(&self) -> &Position {
+        &self.positions[0]
+    }
+
+    /// The position the game currently stands at.
+    pub fn position(&self) -> &Position {
+        &self.positions[self.ply]
+    }
+
+    /// All moves of the game, including those after the current position.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Number of moves leading to the current position.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// The move that led to the current position.
+    pub fn last_move(&self) -> Option<Move> {
+        match self.ply {
+            0 => None,
+            ply => Some(self.moves[ply - 1]),
+        }
+    }
+
+    /// Plays `mv` from the current position and returns the captured piece.
+    /// The move is not checked for legality.
+    pub fn play(&mut self, mv: Move) -> Option<Piece> {
+        self.positions.truncate(self.ply + 1);
+        self.moves.truncate(self.ply);
+        let mut next = self.position().clone();
+        let captured = next.make_move(mv);
+        self.positions.push(next);
+        self.moves.push(mv);
+        self.ply += 1;
+        captured
+    }
+
+    /// Steps one move back. Returns false at the start of the game.
+    pub fn back(&mut self) -> bool {
+        if self.ply == 0 {
+            return false;
+        }
+        self.ply -= 1;
+        true
+    }
+
+    /// Steps one move forward again. Returns false at the last move.
+    pub fn forward(&mut self) -> bool {
+        if self.ply == self.moves.len() {
+            return false;
+        }
+        self.ply += 1;
+        true
+    }
+
+    /// Steps back to the starting position.
+    pub fn rewind(&mut self) {
+        self.ply = 0;
+    }
+
+    /// Pieces captured on the way to the current position.
+    pub fn captures(&self) -> Vec<Piece> {
+        self.moves[..self.ply]
+            .iter()
+            .zip(&self.positions)
+            .filter_map(|(&mv, position)| {
+                position
+                    .capture_square(mv)
+                    .and_then(|square| position.board().get(square))
+            })
+            .collect()
+    }
+
+    /// The moves leading to the current position in Standard Algebraic
+    /// Notation, without those taken back.
+    pub fn san_moves(&self) -> Vec<String> {
+        self.moves[..self.ply]
+            .iter()
+            .zip(&self.positions)
+            .map(|(&mv, position)| position.to_san(mv))
+            .collect()
+    }
+}
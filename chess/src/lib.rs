@@ -0,0 +1,42 @@
+//! Rules of chess without any rendering attached.
+//!
+//! The amethyst frontend in `boardgame` only mirrors what happens here,
+//! so everything in this crate can be used and tested without a window.
+mod bitboard;
+mod board;
+mod check;
+mod clock;
+mod eval;
+mod fen;
+mod game;
+mod movegen;
+mod net;
+mod pgn;
+mod piece;
+mod player;
+mod position;
+mod relay;
+mod rules;
+mod san;
+mod search;
+mod square;
+mod uci;
+
+pub use bitboard::BitPosition;
+pub use board::Board;
+pub use check::GameStatus;
+pub use clock::{Clock, Increment, TimeControl};
+pub use eval::evaluate;
+pub use fen::{FenError, MAX_BOARD_SIZE, START_FEN};
+pub use game::Game;
+pub use movegen::PROMOTIONS;
+pub use net::{read_message, write_message, Connection, Message, PROTOCOL_VERSION};
+pub use pgn::{Pgn, PgnError};
+pub use piece::{Piece, PieceSet, PieceType};
+pub use player::Player;
+pub use position::{CastlingRights, Move, Position};
+pub use relay::serve_relay;
+pub use rules::Rules;
+pub use search::{search, search_until, Limits, SearchResult, MATE};
+pub use square::{parse_square, square_name};
+pub use uci::{position_command, serve_uci, UciEngine};
@@ -0,0 +1,248 @@
+use crate::piece::{Piece, PieceType};
+use crate::player::Player;
+use crate::position::{Move, Position};
+
+pub(crate) const KNIGHT_JUMPS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+/// Pieces a pawn may turn into in standard chess, best first.
+pub const PROMOTIONS: [PieceType; 4] = [
+    PieceType::QUEEN,
+    PieceType::ROOK,
+    PieceType::BISHOP,
+    PieceType::KNIGHT,
+];
+pub(crate) const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+pub(crate) const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+impl Position {
+    /// All moves the piece on `from` can make without leaving its own king
+    /// in check. Empty if the square is empty or off the board, as the
+    /// squares of a move read from a peer or a file may be.
+    pub fn moves_from(&self, from: (u32, u32)) -> Vec<Move> {
+        if !self.board().contains(from.0 as i32, from.1 as i32) {
+            return vec![];
+        }
+        let player = match self.board().get(from) {
+            Some(piece) => piece.player,
+            None => return vec![],
+        };
+        self.pseudo_moves_from(from)
+            .into_iter()
+            .filter(|&mv| !self.leaves_in_check(mv, player))
+            .collect()
+    }
+
+    /// Moves following how the piece on `from` moves, ignoring checks.
+    fn pseudo_moves_from(&self, from: (u32, u32)) -> Vec<Move> {
+        let piece = match self.board().get(from) {
+            Some(piece) => piece,
+            None => return vec![],
+        };
+        let mut moves = vec![];
+        match piece.piece_type {
+            PieceType::KING => {
+                self.steps(from, piece.player, &ROOK_DIRECTIONS, &mut moves);
+                self.steps(from, piece.player, &BISHOP_DIRECTIONS, &mut moves);
+                self.castling_moves(from, piece.player, &mut moves);
+            }
+            PieceType::QUEEN => {
+                self.slides(from, piece.player, &ROOK_DIRECTIONS, &mut moves);
+                self.slides(from, piece.player, &BISHOP_DIRECTIONS, &mut moves);
+            }
+            PieceType::ROOK => self.slides(from, piece.player, &ROOK_DIRECTIONS, &mut moves),
+            PieceType::BISHOP => self.slides(from, piece.player, &BISHOP_DIRECTIONS, &mut moves),
+            PieceType::KNIGHT => self.steps(from, piece.player, &KNIGHT_JUMPS, &mut moves),
+            PieceType::ARCHBISHOP => {
+                self.slides(from, piece.player, &BISHOP_DIRECTIONS, &mut moves);
+                self.steps(from, piece.player, &KNIGHT_JUMPS, &mut moves);
+            }
+            PieceType::CHANCELLOR => {
+                self.slides(from, piece.player, &ROOK_DIRECTIONS, &mut moves);
+                self.steps(from, piece.player, &KNIGHT_JUMPS, &mut moves);
+            }
+            PieceType::PAWN => self.pawn_moves(from, piece.player, &mut moves),
+        }
+        moves
+    }
+
+    /// All moves of the side to move.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let side = self.side_to_move();
+        self.board()
+            .pieces()
+            .filter(|(_, piece)| piece.player == side)
+            .flat_map(|(from, _)| self.moves_from(from))
+            .collect()
+    }
+
+    /// Returns true if `mv` is one of the moves of the piece it starts from.
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.moves_from(mv.from).contains(&mv)
+    }
+
+    fn leaves_in_check(&self, mv: Move, player: Player) -> bool {
+        let mut next = self.clone();
+        next.make_move(mv);
+        next.in_check(player)
+    }
+
+    /// Single steps in each direction onto empty or enemy squares.
+    fn steps(
+        &self,
+        from: (u32, u32),
+        player: Player,
+        deltas: &[(i32, i32)],
+        moves: &mut Vec<Move>,
+    ) {
+        for &(dx, dy) in deltas {
+            let (x, y) = (from.0 as i32 + dx, from.1 as i32 + dy);
+            if self.board().contains(x, y) && self.enterable((x as u32, y as u32), player) {
+                moves.push(Move::new(from, (x as u32, y as u32)));
+            }
+        }
+    }
+
+    /// Rays in each direction up to the first occupied square, which is
+    /// included if it holds an enemy piece.
+    fn slides(
+        &self,
+        from: (u32, u32),
+        player: Player,
+        deltas: &[(i32, i32)],
+        moves: &mut Vec<Move>,
+    ) {
+        for &(dx, dy) in deltas {
+            let (mut x, mut y) = (from.0 as i32 + dx, from.1 as i32 + dy);
+            while self.board().contains(x, y) {
+                let to = (x as u32, y as u32);
+                match self.board().get(to) {
+                    None => moves.push(Move::new(from, to)),
+                    Some(other) => {
+                        if other.player != player {
+                            moves.push(Move::new(from, to));
+                        }
+                        break;
+                    }
+                }
+                x += dx;
+                y += dy;
+            }
+        }
+    }
+
+    fn pawn_moves(&self, from: (u32, u32), player: Player, moves: &mut Vec<Move>) {
+        let forward = self.forward(player);
+        let (x, y) = (from.0 as i32, from.1 as i32 + forward);
+        if !self.board().contains(x, y) {
+            return;
+        }
+        let one = (x as u32, y as u32);
+        if self.board().get(one).is_none() {
+            self.pawn_move(from, one, moves);
+            let two_y = y + forward;
+            if self.double_step()
+                && from.1 == self.pawn_rank(player)
+                && self.board().contains(x, two_y)
+            {
+                let two = (x as u32, two_y as u32);
+                if self.board().get(two).is_none() {
+                    moves.push(Move::new(from, two));
+                }
+            }
+        }
+        for &dx in &[-1, 1] {
+            if !self.board().contains(x + dx, y) {
+                continue;
+            }
+            let to = ((x + dx) as u32, y as u32);
+            match self.board().get(to) {
+                Some(other) if other.player != player => self.pawn_move(from, to, moves),
+                None if self.en_passant() == Some(to) => moves.push(Move::new(from, to)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Adds a pawn move, once per piece of `promotions` it can turn into
+    /// if it reaches the last row.
+    fn pawn_move(&self, from: (u32, u32), to: (u32, u32), moves: &mut Vec<Move>) {
+        let last_rows = [0, self.board().height() - 1];
+        if last_rows.contains(&to.1) {
+            for piece_type in self.promotions().iter() {
+                moves.push(Move::with_promotion(from, to, piece_type));
+            }
+        } else {
+            moves.push(Move::new(from, to));
+        }
+    }
+
+    /// Castling moves the king `castling_distance` squares towards a rook
+    /// that has not moved yet, which lands next to it on the inner side.
+    /// The squares both cross or land on have to be empty and the king may
+    /// neither start in, pass through nor land in check.
+    fn castling_moves(&self, from: (u32, u32), player: Player, moves: &mut Vec<Move>) {
+        let rights = self.castling(player);
+        let rank = self.back_rank(player);
+        if from.1 != rank || self.is_attacked(from, player.opponent()) {
+            return;
+        }
+        let corners = [
+            (rights.king_side, self.board().width() - 1, 1),
+            (rights.queen_side, 0, -1),
+        ];
+        for &(allowed, rook_x, dx) in &corners {
+            let rook = self.board().get((rook_x, rank));
+            if !allowed || rook != Some(Piece::new(player, PieceType::ROOK)) {
+                continue;
+            }
+            let distance = self.castling_distance() as i32;
+            let to = from.0 as i32 + distance * dx;
+            if !self.board().contains(to, rank as i32) {
+                continue;
+            }
+            // the rook lands between the king's squares, so these are all
+            // the squares either of them crosses or lands on
+            let (x, rook_x) = (from.0 as i32, rook_x as i32);
+            let (low, high) = (x.min(rook_x).min(to), x.max(rook_x).max(to));
+            let blocked = (low..=high)
+                .filter(|&square| square != x && square != rook_x)
+                .any(|square| self.board().get((square as u32, rank)).is_some());
+            let attacked = (1..distance)
+                .any(|step| self.is_attacked(((x + step * dx) as u32, rank), player.opponent()));
+            if !blocked && !attacked {
+                moves.push(Move::new(from, (to as u32, rank)));
+            }
+        }
+    }
+
+    /// Direction along `y` the pawns of `player` walk.
+    pub(crate) fn forward(&self, player: Player) -> i32 {
+        match player {
+            Player::SECOND => -1,
+            _ => 1,
+        }
+    }
+
+    /// Row the pawns of `player` start on and may double step from.
+    pub(crate) fn pawn_rank(&self, player: Player) -> u32 {
+        match player {
+            Player::SECOND => self.board().height() - 2,
+            _ => 1,
+        }
+    }
+
+    fn enterable(&self, to: (u32, u32), player: Player) -> bool {
+        match self.board().get(to) {
+            None => true,
+            Some(other) => other.player != player,
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Two players on different machines, playing over TCP.
+//!
+//! Every message is a 4 byte big-endian length followed by that many bytes
+//! of UTF-8 text, a single line in the style of UCI:
+//!
+//! - `hello <version> <w|b> <promotions> <pawn steps> <castling> <fen>`
+//!   opens the game. The host sends the side the guest plays and the
+//!   position to start from, the guest answers with the side of the host
+//!   and the same position. The rules of the variant come before the FEN, which cannot
+//!   hold them: the letters of the pieces pawns promote to, e.g. `QRBN`,
+//!   `double` or `single` for whether pawns may double step and the
+//!   number of squares the king moves when castling.
+//! - `move <uci>`
+//! - `resign`
+//! - `draw offer`, answered by `draw accept` or by playing on.
+//! - `chat <text>`
+//!
+//! Spectators of a relay, see `serve_relay`, get a `hello` with `-` for
+//! the side, followed by `moves <uci>...` with the moves played so far.
+//! After that they are sent each new `move`, the `chat` of the players and
+//! `end <result>` if the game ends by resignation or agreement.
+use crate::check::GameStatus;
+use crate::game::Game;
+use crate::player::Player;
+use crate::position::{Move, Position};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+
+/// Version sent in the handshake, both ends have to speak the same.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Longest message accepted, anything longer is taken as garbage.
+const MAX_LENGTH: usize = 1 << 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hello {
+        version: u32,
+        /// The side the receiver plays, `Player::NONE` for spectators.
+        side: Player,
+        start: Position,
+    },
+    /// A move of the sender. It is not checked until it is played.
+    Move(Move),
+    Resign,
+    DrawOffer,
+    DrawAccept,
+    Chat(String),
+    /// All moves played before a spectator joined.
+    Moves(Vec<Move>),
+    /// A resignation or agreed draw, told to spectators.
+    Ended(GameStatus),
+}
+
+impl Message {
+    /// Reads a message in the text form `to_string` writes.
+    pub fn parse(text: &str) -> Option<Message> {
+        let (command, args) = match text.find(' ') {
+            Some(space) => (&text[..space], &text[space + 1..]),
+            None => (text, ""),
+        };
+        match (command, args) {
+            ("hello", args) => {
+                let mut fields = args.splitn(6, ' ');
+                let version = fields.next()?.parse().ok()?;
+                let side = match fields.next()? {
+                    "w" => Player::FIRST,
+                    "b" => Player::SECOND,
+                    "-" => Player::NONE,
+                    _ => return None,
+                };
+                let rules = [fields.next()?, fields.next()?, fields.next()?].join(" ");
+                let start = Position::from_fen(fields.next()?)
+                    .ok()?
+                    .with_variant_rules(&rules)?;
+                Some(Message::Hello {
+                    version,
+                    side,
+                    start,
+                })
+            }
+            ("move", uci) => Move::from_uci(uci).map(Message::Move),
+            ("resign", "") => Some(Message::Resign),
+            ("draw", "offer") => Some(Message::DrawOffer),
+            ("draw", "accept") => Some(Message::DrawAccept),
+            ("chat", text) => Some(Message::Chat(text.to_string())),
+            ("moves", moves) => moves
+                .split_whitespace()
+                .map(Move::from_uci)
+                .collect::<Option<_>>()
+                .map(Message::Moves),
+            ("end", "1-0") => Some(Message::Ended(GameStatus::Resignation(Player::FIRST))),
+            ("end", "0-1") => Some(Message::Ended(GameStatus::Resignation(Player::SECOND))),
+            ("end", "1/2-1/2") => Some(Message::Ended(GameStatus::DrawAgreed)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Message::Hello {
+                version,
+                side,
+                start,
+            } => {
+                let side = match *side {
+                    Player::FIRST => "w",
+                    Player::SECOND => "b",
+                    Player::NONE => "-",
+                };
+                write!(f, "hello {} {} {} {}", version, side, start.variant_rules(), start.to_fen())
+            }
+            Message::Move(mv) => write!(f, "move {}", mv.to_uci()),
+            Message::Resign => write!(f, "resign"),
+            Message::DrawOffer => write!(f, "draw offer"),
+            Message::DrawAccept => write!(f, "draw accept"),
+            // a chat line stays one line
+            Message::Chat(text) => write!(f, "chat {}", text.replace('\n', " ")),
+            Message::Moves(moves) => {
+                write!(f, "moves")?;
+                for mv in moves {
+                    write!(f, " {}", mv.to_uci())?;
+                }
+                Ok(())
+            }
+            Message::Ended(status) => write!(f, "end {}", status.result()),
+        }
+    }
+}
+
+/// Writes `message` with its length in front.
+pub fn write_message<W: Write>(output: &mut W, message: &Message) -> io::Result<()> {
+    let text = message.to_string();
+    if text.len() > MAX_LENGTH {
+        return Err(invalid(format!("message of {} bytes is too long", text.len())));
+    }
+    output.write_all(&(text.len() as u32).to_be_bytes())?;
+    output.write_all(text.as_bytes())?;
+    output.flush()
+}
+
+/// Reads the next message, waiting until all of it has arrived.
+pub fn read_message<R: Read>(input: &mut R) -> io::Result<Message> {
+    let mut length = [0; 4];
+    input.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_LENGTH {
+        return Err(invalid(format!("message of {} bytes is too long", length)));
+    }
+    let mut text = vec![0; length];
+    input.read_exact(&mut text)?;
+    let text = String::from_utf8(text).map_err(|_| invalid("message is not UTF-8"))?;
+    Message::parse(&text).ok_or_else(|| invalid(format!("unknown message '{}'", text)))
+}
+
+/// One end of a game between two machines, after both agreed on it.
+/// Dropping either end closes the connection.
+pub struct Connection {
+    stream: TcpStream,
+    /// The side played on this end.
+    pub side: Player,
+    /// Position the game starts from, chosen by the host.
+    pub start: Position,
+}
+
+impl Connection {
+    /// Waits for a guest on `listener` and offers it a game from `start`
+    /// in which the guest plays `guest`.
+    pub fn accept(listener: &TcpListener, start: &Position, guest: Player) -> io::Result<Connection> {
+        let (stream, _) = listener.accept()?;
+        Connection::offer(stream, start, guest)
+    }
+
+    /// Offers the guest that connected on `stream` a game from `start` in
+    /// which the guest plays `guest`, for hosts accepting it themselves.
+    pub fn offer(stream: TcpStream, start: &Position, guest: Player) -> io::Result<Connection> {
+        let mut connection = Connection {
+            stream,
+            side: guest.opponent(),
+            start: start.clone(),
+        };
+        connection.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            side: guest,
+            start: start.clone(),
+        })?;
+        match connection.receive()? {
+            Message::Hello {
+                version,
+                side,
+                start: answer,
+            } if version == PROTOCOL_VERSION && side == connection.side && answer == *start => {
+                Ok(connection)
+            }
+            message => Err(invalid(format!("guest did not agree to the game: {}", message))),
+        }
+    }
+
+    /// Joins the game of the host at `address`.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Connection> {
+        let stream = TcpStream::connect(address)?;
+        let mut connection = match read_message(&mut &stream)? {
+            Message::Hello {
+                side: Player::NONE, ..
+            } => return Err(invalid("the host only takes spectators")),
+            Message::Hello {
+                version,
+                side,
+                start,
+            } if version == PROTOCOL_VERSION => Connection {
+                stream,
+                side,
+                start,
+            },
+            Message::Hello { version, .. } => {
+                return Err(invalid(format!(
+                    "host speaks version {}, we speak {}",
+                    version, PROTOCOL_VERSION
+                )))
+            }
+            message => return Err(invalid(format!("expected hello, got {}", message))),
+        };
+        connection.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            side: connection.side.opponent(),
+            start: connection.start.clone(),
+        })?;
+        Ok(connection)
+    }
+
+    /// Watches the game relayed at `address`. Returns the game as far as it
+    /// has been played, the following moves arrive as messages.
+    pub fn watch<A: ToSocketAddrs>(address: A) -> io::Result<(Connection, Game)> {
+        let stream = TcpStream::connect(address)?;
+        let mut connection = match read_message(&mut &stream)? {
+            Message::Hello {
+                version,
+                side: Player::NONE,
+                start,
+            } if version == PROTOCOL_VERSION => Connection {
+                stream,
+                side: Player::NONE,
+                start,
+            },
+            message => return Err(invalid(format!("expected a spectator hello, got {}", message))),
+        };
+        let mut game = Game::new(connection.start.clone());
+        match connection.receive()? {
+            Message::Moves(moves) => {
+                for mv in moves {
+                    if !game.position().is_legal(mv) {
+                        return Err(invalid(format!("relay sent illegal move {}", mv.to_uci())));
+                    }
+                    game.play(mv);
+                }
+            }
+            message => return Err(invalid(format!("expected the moves, got {}", message))),
+        }
+        Ok((connection, game))
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        write_message(&mut self.stream, message)
+    }
+
+    /// Waits for the next message of the other end.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        read_message(&mut self.stream)
+    }
+
+    /// A second handle to the same connection, so one thread can wait for
+    /// messages while another sends.
+    pub fn try_clone(&self) -> io::Result<Connection> {
+        Ok(Connection {
+            stream: self.stream.try_clone()?,
+            side: self.side,
+            start: self.start.clone(),
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // wakes up a thread still waiting on a clone
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn invalid<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,244 @@
+use crate::fen::FenError;
+use crate::game::Game;
+use crate::player::Player;
+use crate::position::Position;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnError {
+    /// A line starting with `[` that is not a `[Name "value"]` tag.
+    InvalidTag(String),
+    Fen(FenError),
+    /// A `Rules` tag that is not written like `Position::variant_rules`.
+    InvalidRules(String),
+    /// A move that is not legal at its point in the game.
+    IllegalMove(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::InvalidTag(line) => write!(f, "invalid PGN tag '{}'", line),
+            PgnError::Fen(err) => write!(f, "invalid FEN tag: {}", err),
+            PgnError::InvalidRules(rules) => write!(f, "invalid Rules tag '{}'", rules),
+            PgnError::IllegalMove(san) => write!(f, "illegal move '{}' in PGN", san),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+impl From<FenError> for PgnError {
+    fn from(err: FenError) -> PgnError {
+        PgnError::Fen(err)
+    }
+}
+
+/// A game in Portable Game Notation: tag pairs followed by the moves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pgn {
+    /// Tag pairs in the order they are written.
+    pub tags: Vec<(String, String)>,
+    pub game: Game,
+}
+
+impl Pgn {
+    /// Wraps `game` with the seven required tags, unknown values left as `?`.
+    /// Games not starting from the usual position also get a `FEN` tag,
+    /// those of variants a `Rules` tag with what the FEN cannot hold, see
+    /// `Position::variant_rules`.
+    pub fn new(game: Game) -> Pgn {
+        let mut pgn = Pgn { tags: vec![], game };
+        for &name in &["Event", "Site", "Date", "Round", "White", "Black"] {
+            pgn.set_tag(name, "?");
+        }
+        let result = pgn.result().to_string();
+        pgn.set_tag("Result", &result);
+        if *pgn.game.start() != Position::standard() {
+            let fen = pgn.game.start().to_fen();
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", &fen);
+        }
+        if !pgn.game.start().standard_rules() {
+            let rules = pgn.game.start().variant_rules();
+            pgn.set_tag("Rules", &rules);
+        }
+        pgn
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets a tag, replacing its value if it exists already.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Result of the game as written after the moves: `1-0`, `0-1`,
+    /// `1/2-1/2`, or `*` while it is still going on. Moves taken back are
+    /// not written.
+    pub fn result(&self) -> &'static str {
+        self.game.position().status().result()
+    }
+
+    /// Reads the first game of a PGN file. Comments, variations and
+    /// numeric annotations are skipped. The game is left at its last move.
+    pub fn parse(text: &str) -> Result<Pgn, PgnError> {
+        let mut tags = vec![];
+        let mut movetext = String::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                tags.push(parse_tag(line).ok_or_else(|| PgnError::InvalidTag(line.to_string()))?);
+            } else if !line.starts_with('%') {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let mut start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen)?,
+            None => Position::standard(),
+        };
+        if let Some((_, rules)) = tags.iter().find(|(name, _)| name == "Rules") {
+            start = start
+                .with_variant_rules(rules)
+                .ok_or_else(|| PgnError::InvalidRules(rules.clone()))?;
+        }
+        let mut game = Game::new(start);
+        for token in tokens(&movetext) {
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token.as_str()) {
+                break;
+            }
+            let san = strip_move_number(&token);
+            if san.is_empty() {
+                continue;
+            }
+            let mv = game
+                .position()
+                .parse_san(san)
+                .ok_or_else(|| PgnError::IllegalMove(san.to_string()))?;
+            game.play(mv);
+        }
+        Ok(Pgn { tags, game })
+    }
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(
+                f,
+                "[{} \"{}\"]",
+                name,
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+        writeln!(f)?;
+
+        let start = self.game.start();
+        let mut number = start.fullmove_number();
+        let mut side = start.side_to_move();
+        let mut words = vec![];
+        if side == Player::SECOND && self.game.ply() > 0 {
+            words.push(format!("{}...", number));
+        }
+        for san in self.game.san_moves() {
+            if side == Player::FIRST {
+                words.push(format!("{}.", number));
+            } else {
+                number += 1;
+            }
+            words.push(san);
+            side = side.opponent();
+        }
+        words.push(self.tag("Result").unwrap_or("*").to_string());
+
+        // keep the lines below 80 characters
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + word.len() >= 80 {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+/// The move of `12.e4` or `12...e4`, `12.` itself becoming empty. Tokens
+/// without a move number, like `0-0`, are left as they are.
+fn strip_move_number(token: &str) -> &str {
+    let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    match token[digits..].strip_prefix('.') {
+        Some(rest) if digits > 0 => rest.trim_start_matches('.'),
+        _ => token,
+    }
+}
+
+/// Reads `[Name "value"]`.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let space = inner.find(char::is_whitespace)?;
+    let (name, value) = inner.split_at(space);
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((
+        name.to_string(),
+        value.replace("\\\"", "\"").replace("\\\\", "\\"),
+    ))
+}
+
+/// Splits the movetext into words, leaving out `{comments}`, `; comments`,
+/// `(variations)` and `$1` style annotations.
+fn tokens(movetext: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut word = String::new();
+    let mut chars = movetext.chars();
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if depth == 0 => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            ';' if depth == 0 => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth > 0 => {}
+            c if c.is_whitespace() => {}
+            c => {
+                word.push(c);
+                continue;
+            }
+        }
+        if !word.is_empty() {
+            tokens.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens.retain(|token| !token.starts_with('$'));
+    tokens
+}
@@ -0,0 +1,141 @@
+use crate::player::Player;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PieceType {
+    KING,
+    QUEEN,
+    ROOK,
+    KNIGHT,
+    BISHOP,
+    PAWN,
+    /// Moves like a bishop or a knight, from Capablanca chess.
+    ARCHBISHOP,
+    /// Moves like a rook or a knight, from Capablanca chess.
+    CHANCELLOR,
+}
+
+impl PieceType {
+    /// Upper case letter used for the piece in FEN and algebraic notation.
+    pub fn to_char(self) -> char {
+        match self {
+            PieceType::KING => 'K',
+            PieceType::QUEEN => 'Q',
+            PieceType::ROOK => 'R',
+            PieceType::KNIGHT => 'N',
+            PieceType::BISHOP => 'B',
+            PieceType::PAWN => 'P',
+            PieceType::ARCHBISHOP => 'A',
+            PieceType::CHANCELLOR => 'C',
+        }
+    }
+
+    /// Reads the letter of a piece in either case.
+    pub fn from_char(c: char) -> Option<PieceType> {
+        match c.to_ascii_uppercase() {
+            'K' => Some(PieceType::KING),
+            'Q' => Some(PieceType::QUEEN),
+            'R' => Some(PieceType::ROOK),
+            'N' => Some(PieceType::KNIGHT),
+            'B' => Some(PieceType::BISHOP),
+            'P' => Some(PieceType::PAWN),
+            'A' => Some(PieceType::ARCHBISHOP),
+            'C' => Some(PieceType::CHANCELLOR),
+            _ => None,
+        }
+    }
+}
+
+/// Every piece type, most valuable first.
+const BY_VALUE: [PieceType; 8] = [
+    PieceType::KING,
+    PieceType::QUEEN,
+    PieceType::CHANCELLOR,
+    PieceType::ARCHBISHOP,
+    PieceType::ROOK,
+    PieceType::BISHOP,
+    PieceType::KNIGHT,
+    PieceType::PAWN,
+];
+
+/// A set of piece types, such as the ones pawns may promote to. Written as
+/// their letters, e.g. `QRBN`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PieceSet(u8);
+
+impl PieceSet {
+    pub fn of(types: &[PieceType]) -> PieceSet {
+        PieceSet(types.iter().fold(0, |bits, &t| bits | PieceSet::bit(t)))
+    }
+
+    pub fn contains(self, piece_type: PieceType) -> bool {
+        self.0 & PieceSet::bit(piece_type) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The types in the set, most valuable first.
+    pub fn iter(self) -> impl Iterator<Item = PieceType> {
+        BY_VALUE.iter().copied().filter(move |&t| self.contains(t))
+    }
+
+    /// Reads the letters of the types in either case, `-` for none.
+    pub fn parse(letters: &str) -> Option<PieceSet> {
+        if letters == "-" {
+            return Some(PieceSet::default());
+        }
+        let types = letters
+            .chars()
+            .map(PieceType::from_char)
+            .collect::<Option<Vec<_>>>()?;
+        Some(PieceSet::of(&types))
+    }
+
+    fn bit(piece_type: PieceType) -> u8 {
+        1 << BY_VALUE.iter().position(|&t| t == piece_type).unwrap()
+    }
+}
+
+impl fmt::Display for PieceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "-");
+        }
+        for piece_type in self.iter() {
+            write!(f, "{}", piece_type.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub player: Player,
+    pub piece_type: PieceType,
+}
+
+impl Piece {
+    pub fn new(player: Player, piece_type: PieceType) -> Piece {
+        Piece { player, piece_type }
+    }
+
+    /// FEN letter of the piece, upper case for `Player::FIRST`.
+    pub fn to_char(self) -> char {
+        match self.player {
+            Player::FIRST => self.piece_type.to_char(),
+            _ => self.piece_type.to_char().to_ascii_lowercase(),
+        }
+    }
+
+    /// Reads a FEN letter, upper case meaning `Player::FIRST`.
+    pub fn from_char(c: char) -> Option<Piece> {
+        let player = if c.is_ascii_uppercase() {
+            Player::FIRST
+        } else {
+            Player::SECOND
+        };
+        PieceType::from_char(c).map(|piece_type| Piece::new(player, piece_type))
+    }
+}
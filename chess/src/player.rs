@@ -0,0 +1,26 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Player {
+    FIRST,
+    SECOND,
+    NONE,
+}
+
+impl Player {
+    /// The player sitting on the other side of the board.
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::FIRST => Player::SECOND,
+            Player::SECOND => Player::FIRST,
+            Player::NONE => Player::NONE,
+        }
+    }
+
+    /// Slot of the player in per player arrays.
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Player::FIRST => 0,
+            Player::SECOND => 1,
+            Player::NONE => panic!("Player::NONE has no index"),
+        }
+    }
+}
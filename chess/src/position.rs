@@ -0,0 +1,316 @@
+use crate::board::Board;
+use crate::movegen::PROMOTIONS;
+use crate::piece::{Piece, PieceSet, PieceType};
+use crate::player::Player;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+    /// What a pawn turns into when it reaches the last row.
+    pub promotion: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(from: (u32, u32), to: (u32, u32)) -> Move {
+        Move {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    pub fn with_promotion(from: (u32, u32), to: (u32, u32), piece_type: PieceType) -> Move {
+        Move {
+            from,
+            to,
+            promotion: Some(piece_type),
+        }
+    }
+}
+
+/// Which sides a player may still castle to. The king side is towards the
+/// highest `x`, the queen side towards `x = 0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CastlingRights {
+    pub king_side: bool,
+    pub queen_side: bool,
+}
+
+impl CastlingRights {
+    pub fn both() -> CastlingRights {
+        CastlingRights {
+            king_side: true,
+            queen_side: true,
+        }
+    }
+}
+
+/// Everything needed to continue a game: the board, who moves next and the
+/// bits of history the rules depend on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    board: Board,
+    side_to_move: Player,
+    castling: [CastlingRights; 2],
+    /// Square a pawn skipped over with its double step in the last move.
+    en_passant: Option<(u32, u32)>,
+    /// Moves since the last capture or pawn move.
+    halfmove_clock: u32,
+    /// Starts at 1 and goes up after every move of `Player::SECOND`.
+    fullmove_number: u32,
+    /// Pieces a pawn may turn into, `PROMOTIONS` unless a variant says otherwise.
+    promotions: PieceSet,
+    /// Whether pawns may move two squares from their starting row.
+    double_step: bool,
+    /// Squares the king moves when castling, 2 unless a variant says otherwise.
+    castling_distance: u32,
+}
+
+impl Position {
+    /// A position without castling rights or en passant square, played by
+    /// the usual rules for promotions and pawn steps.
+    pub fn new(board: Board, side_to_move: Player) -> Position {
+        Position {
+            board,
+            side_to_move,
+            castling: [CastlingRights::default(); 2],
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            promotions: PieceSet::of(&PROMOTIONS),
+            double_step: true,
+            castling_distance: 2,
+        }
+    }
+
+    /// The usual starting position. `Player::FIRST` plays the white pieces
+    /// from the bottom rows and moves first.
+    pub fn standard() -> Position {
+        use PieceType::*;
+        let back_rank = [ROOK, KNIGHT, BISHOP, QUEEN, KING, BISHOP, KNIGHT, ROOK];
+        let mut board = Board::new(8, 8);
+        for (x, piece_type) in back_rank.iter().enumerate() {
+            let x = x as u32;
+            board.set((x, 0), Some(Piece::new(Player::FIRST, *piece_type)));
+            board.set((x, 1), Some(Piece::new(Player::FIRST, PAWN)));
+            board.set((x, 6), Some(Piece::new(Player::SECOND, PAWN)));
+            board.set((x, 7), Some(Piece::new(Player::SECOND, *piece_type)));
+        }
+        let mut position = Position::new(board, Player::FIRST);
+        position.set_castling(Player::FIRST, CastlingRights::both());
+        position.set_castling(Player::SECOND, CastlingRights::both());
+        position
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn side_to_move(&self) -> Player {
+        self.side_to_move
+    }
+
+    pub fn castling(&self, player: Player) -> CastlingRights {
+        self.castling[player.index()]
+    }
+
+    pub fn set_castling(&mut self, player: Player, rights: CastlingRights) {
+        self.castling[player.index()] = rights;
+    }
+
+    pub fn en_passant(&self) -> Option<(u32, u32)> {
+        self.en_passant
+    }
+
+    pub fn set_en_passant(&mut self, square: Option<(u32, u32)>) {
+        self.en_passant = square;
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Sets the move counters, as read from a FEN string for example.
+    pub fn set_move_counters(&mut self, halfmove_clock: u32, fullmove_number: u32) {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+    }
+
+    pub fn promotions(&self) -> PieceSet {
+        self.promotions
+    }
+
+    /// Changes what pawns may promote to, for variants with other pieces.
+    pub fn set_promotions(&mut self, promotions: PieceSet) {
+        self.promotions = promotions;
+    }
+
+    pub fn double_step(&self) -> bool {
+        self.double_step
+    }
+
+    /// Allows or forbids the double step of pawns, which small boards
+    /// usually leave out.
+    pub fn set_double_step(&mut self, double_step: bool) {
+        self.double_step = double_step;
+    }
+
+    pub fn castling_distance(&self) -> u32 {
+        self.castling_distance
+    }
+
+    /// Changes how far the king moves when castling, e.g. 3 on the wider
+    /// board of Capablanca chess. The rook lands on the square the king
+    /// passed last. Has to be at least 2, a king moving one square does
+    /// not castle.
+    pub fn set_castling_distance(&mut self, distance: u32) {
+        self.castling_distance = distance;
+    }
+
+    /// Returns true if the position is played by the rules of standard
+    /// chess, whatever the size of the board.
+    pub fn standard_rules(&self) -> bool {
+        self.double_step && self.promotions == PieceSet::of(&PROMOTIONS) && self.castling_distance == 2
+    }
+
+    /// The rules a FEN cannot hold, as the network handshake and the `Rules`
+    /// tag of a PGN write them: the pieces pawns promote to, `double` or
+    /// `single` for the pawn steps and the castling distance, e.g. `QRBN double 2`.
+    pub fn variant_rules(&self) -> String {
+        let steps = if self.double_step { "double" } else { "single" };
+        format!("{} {} {}", self.promotions, steps, self.castling_distance)
+    }
+
+    /// The position played by `rules` as `variant_rules` writes them, `None`
+    /// if they cannot be read.
+    pub fn with_variant_rules(mut self, rules: &str) -> Option<Position> {
+        let mut fields = rules.split(' ');
+        self.promotions = PieceSet::parse(fields.next()?)?;
+        self.double_step = match fields.next()? {
+            "double" => true,
+            "single" => false,
+            _ => return None,
+        };
+        self.castling_distance = match fields.next()?.parse().ok()? {
+            distance if distance >= 2 => distance,
+            _ => return None,
+        };
+        match fields.next() {
+            Some(_) => None,
+            None => Some(self),
+        }
+    }
+
+    /// Row the pieces of `player` start on.
+    pub fn back_rank(&self, player: Player) -> u32 {
+        match player {
+            Player::SECOND => self.board.height() - 1,
+            _ => 0,
+        }
+    }
+
+    /// The move of the rook if `mv` castles.
+    pub fn castling_rook(&self, mv: Move) -> Option<Move> {
+        match self.board.get(mv.from) {
+            Some(piece) if piece.piece_type == PieceType::KING => {}
+            _ => return None,
+        }
+        let (y, distance) = (mv.from.1, self.castling_distance);
+        if mv.to.1 != y {
+            None
+        } else if mv.to.0 == mv.from.0 + distance {
+            Some(Move::new((self.board.width() - 1, y), (mv.to.0 - 1, y)))
+        } else if mv.to.0 + distance == mv.from.0 {
+            Some(Move::new((0, y), (mv.to.0 + 1, y)))
+        } else {
+            None
+        }
+    }
+
+    /// Square of the piece `mv` would capture. Differs from `mv.to` only for
+    /// en passant captures.
+    pub fn capture_square(&self, mv: Move) -> Option<(u32, u32)> {
+        if self.board.get(mv.to).is_some() {
+            return Some(mv.to);
+        }
+        match self.board.get(mv.from) {
+            Some(piece)
+                if piece.piece_type == PieceType::PAWN
+                    && mv.from.0 != mv.to.0
+                    && Some(mv.to) == self.en_passant =>
+            {
+                Some((mv.to.0, mv.from.1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Moves the piece on `mv.from` to `mv.to` and hands the turn over.
+    /// The captured piece, if any, is returned.
+    ///
+    /// Castling also moves the rook, en passant removes the passed pawn and
+    /// a promotion replaces the pawn. Whether the move is legal is not checked.
+    pub fn make_move(&mut self, mv: Move) -> Option<Piece> {
+        let piece = self.board.get(mv.from)?;
+        let captured = self.capture_square(mv).and_then(|square| {
+            let captured = self.board.get(square);
+            self.board.set(square, None);
+            captured
+        });
+        if let Some(rook) = self.castling_rook(mv) {
+            let rook_piece = self.board.get(rook.from);
+            self.board.set(rook.from, None);
+            self.board.set(rook.to, rook_piece);
+        }
+        self.board.set(mv.from, None);
+        let placed = match mv.promotion {
+            Some(piece_type) => Piece::new(piece.player, piece_type),
+            None => piece,
+        };
+        self.board.set(mv.to, Some(placed));
+
+        self.en_passant = None;
+        if piece.piece_type == PieceType::PAWN && (mv.from.1 as i32 - mv.to.1 as i32).abs() == 2 {
+            self.en_passant = Some((mv.from.0, (mv.from.1 + mv.to.1) / 2));
+        }
+        self.update_castling(piece, mv);
+
+        if captured.is_some() || piece.piece_type == PieceType::PAWN {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Player::SECOND {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opponent();
+        captured
+    }
+
+    /// Moving the king gives up both castling rights, moving a rook away
+    /// from its corner or having it captured there gives up that side.
+    fn update_castling(&mut self, piece: Piece, mv: Move) {
+        if piece.piece_type == PieceType::KING {
+            self.set_castling(piece.player, CastlingRights::default());
+        }
+        let last = self.board.width() - 1;
+        for &player in &[Player::FIRST, Player::SECOND] {
+            let rank = self.back_rank(player);
+            let mut rights = self.castling(player);
+            for &square in &[mv.from, mv.to] {
+                if square == (0, rank) {
+                    rights.queen_side = false;
+                }
+                if square == (last, rank) {
+                    rights.king_side = false;
+                }
+            }
+            self.set_castling(player, rights);
+        }
+    }
+}
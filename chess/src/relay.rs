@@ -0,0 +1,192 @@
+//! A game between two players hosted on a third machine, which anyone may
+//! watch. The players join as they would join another player, see
+//! `Connection::connect`, spectators with `Connection::watch`.
+use crate::check::GameStatus;
+use crate::game::Game;
+use crate::net::{write_message, Connection, Message, PROTOCOL_VERSION};
+use crate::player::Player;
+use crate::position::Position;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How often the spectator listener looks whether the game is over.
+const POLL: Duration = Duration::from_millis(50);
+/// How long a spectator may keep a message waiting before it is dropped,
+/// so one that stopped reading does not hold up the game.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The game as the spectators see it.
+struct Broadcast {
+    game: Game,
+    spectators: Vec<TcpStream>,
+}
+
+impl Broadcast {
+    /// Sends `message` to every spectator, forgetting those who left or
+    /// did not take it in time.
+    fn send(&mut self, message: &Message) {
+        self.spectators
+            .retain(|mut stream| write_message(&mut stream, message).is_ok());
+    }
+
+    /// Greets a new spectator with the game so far.
+    fn join(&mut self, mut stream: TcpStream) {
+        if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            return;
+        }
+        let hello = Message::Hello {
+            version: PROTOCOL_VERSION,
+            side: Player::NONE,
+            start: self.game.start().clone(),
+        };
+        let moves = Message::Moves(self.game.moves().to_vec());
+        if write_message(&mut stream, &hello).is_ok() && write_message(&mut stream, &moves).is_ok() {
+            self.spectators.push(stream);
+        }
+    }
+}
+
+/// Runs a game from `start` between the first two players connecting to
+/// `players`, the first one playing white. Everyone connecting to
+/// `spectators` is shown the game. Moves are checked before they are
+/// passed on; a player sending an illegal one or leaving loses the game,
+/// also when that is only noticed passing a message on to them.
+/// Returns the game and how it ended once it is over.
+pub fn serve_relay(
+    players: &TcpListener,
+    spectators: TcpListener,
+    start: Position,
+) -> io::Result<(Game, GameStatus)> {
+    let broadcast = Arc::new(Mutex::new(Broadcast {
+        game: Game::new(start.clone()),
+        spectators: vec![],
+    }));
+    let over = Arc::new(AtomicBool::new(false));
+    let watching = {
+        let broadcast = Arc::clone(&broadcast);
+        let over = Arc::clone(&over);
+        spectators.set_nonblocking(true)?;
+        thread::spawn(move || {
+            while !over.load(Ordering::Relaxed) {
+                match spectators.accept() {
+                    Ok((stream, _)) => {
+                        if stream.set_nonblocking(false).is_ok() {
+                            broadcast.lock().unwrap().join(stream);
+                        }
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL),
+                    Err(_) => return,
+                }
+            }
+        })
+    };
+
+    let result = relay(players, &start, &broadcast);
+    over.store(true, Ordering::Relaxed);
+    let _ = watching.join();
+    let status = result?;
+    let mut broadcast = broadcast.lock().unwrap();
+    if let GameStatus::Resignation(_) | GameStatus::DrawAgreed = status {
+        broadcast.send(&Message::Ended(status));
+    }
+    Ok((broadcast.game.clone(), status))
+}
+
+/// Passes the messages of both players on until the game is over.
+fn relay(players: &TcpListener, start: &Position, broadcast: &Mutex<Broadcast>) -> io::Result<GameStatus> {
+    let mut white = Connection::accept(players, start, Player::FIRST)?;
+    let mut black = Connection::accept(players, start, Player::SECOND)?;
+    let (sender, messages) = channel();
+    for (player, connection) in [(Player::FIRST, &white), (Player::SECOND, &black)].iter() {
+        let mut reader = connection.try_clone()?;
+        let sender = sender.clone();
+        let player = *player;
+        thread::spawn(move || loop {
+            let message = reader.receive();
+            let failed = message.is_err();
+            if sender.send((player, message)).is_err() || failed {
+                return;
+            }
+        });
+    }
+    drop(sender);
+
+    let mut draw_offer = None;
+    for (player, message) in messages {
+        let other = match player {
+            Player::FIRST => &mut black,
+            _ => &mut white,
+        };
+        let name = if player == Player::FIRST { "White" } else { "Black" };
+        let message = match message {
+            Ok(message) => message,
+            // leaving an unfinished game gives it up
+            Err(_) => {
+                let _ = other.send(&Message::Resign);
+                return Ok(GameStatus::Resignation(player.opponent()));
+            }
+        };
+        // the broadcast is let go before passing anything on to the other
+        // player, one that stopped reading must not keep spectators out
+        match message {
+            Message::Move(mv) => {
+                let status = {
+                    let mut broadcast = broadcast.lock().unwrap();
+                    let position = broadcast.game.position();
+                    if position.side_to_move() == player && position.is_legal(mv) {
+                        broadcast.game.play(mv);
+                        broadcast.send(&Message::Move(mv));
+                        Some(broadcast.game.position().status())
+                    } else {
+                        None
+                    }
+                };
+                let status = match status {
+                    Some(status) => status,
+                    None => {
+                        let _ = other.send(&Message::Resign);
+                        return Ok(GameStatus::Resignation(player.opponent()));
+                    }
+                };
+                draw_offer = None;
+                if status != GameStatus::Ongoing {
+                    let _ = other.send(&Message::Move(mv));
+                    return Ok(status);
+                }
+                // the opponent is gone, which gives the game up
+                if other.send(&Message::Move(mv)).is_err() {
+                    return Ok(GameStatus::Resignation(player));
+                }
+            }
+            Message::Resign => {
+                let _ = other.send(&Message::Resign);
+                return Ok(GameStatus::Resignation(player.opponent()));
+            }
+            Message::DrawOffer => {
+                draw_offer = Some(player);
+                if other.send(&Message::DrawOffer).is_err() {
+                    return Ok(GameStatus::Resignation(player));
+                }
+            }
+            Message::DrawAccept if draw_offer == Some(player.opponent()) => {
+                let _ = other.send(&Message::DrawAccept);
+                return Ok(GameStatus::DrawAgreed);
+            }
+            Message::Chat(text) => {
+                if other.send(&Message::Chat(text.clone())).is_err() {
+                    return Ok(GameStatus::Resignation(player));
+                }
+                let chat = Message::Chat(format!("{}: {}", name, text));
+                broadcast.lock().unwrap().send(&chat);
+            }
+            // nothing else is the business of a player
+            _ => {}
+        }
+    }
+    Ok(broadcast.lock().unwrap().game.position().status())
+}
@@ -0,0 +1,107 @@
+//! The rules as seen by search and perft, so both run on the general
+//! `Position` as well as on the faster 8x8 `BitPosition`.
+use crate::check::GameStatus;
+use crate::eval;
+use crate::piece::Piece;
+use crate::player::Player;
+use crate::position::{Move, Position};
+
+pub trait Rules: Clone {
+    fn side_to_move(&self) -> Player;
+
+    fn piece_at(&self, square: (u32, u32)) -> Option<Piece>;
+
+    /// All moves of the side to move.
+    fn legal_moves(&self) -> Vec<Move>;
+
+    /// Plays `mv` without checking it and returns the captured piece.
+    fn make_move(&mut self, mv: Move) -> Option<Piece>;
+
+    /// Returns true if the king of `player` is attacked.
+    fn in_check(&self, player: Player) -> bool;
+
+    /// Square of the piece `mv` would capture, see `Position::capture_square`.
+    fn capture_square(&self, mv: Move) -> Option<(u32, u32)>;
+
+    /// Centipawns from the side to move's point of view, see `evaluate`.
+    fn evaluate(&self) -> i32;
+
+    /// Whether the side to move can still play on.
+    fn status(&self) -> GameStatus {
+        let side = self.side_to_move();
+        if !self.legal_moves().is_empty() {
+            GameStatus::Ongoing
+        } else if self.in_check(side) {
+            GameStatus::Checkmate(side.opponent())
+        } else {
+            GameStatus::Stalemate
+        }
+    }
+
+    /// Number of move sequences of exactly `depth` plies, the usual way to
+    /// check a move generator against known numbers.
+    fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .into_iter()
+            .map(|mv| {
+                let mut next = self.clone();
+                next.make_move(mv);
+                next.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// `perft` split up by the first move, to find the move a wrong count
+    /// comes from by comparing with another engine.
+    fn divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let mut next = self.clone();
+                next.make_move(mv);
+                (mv, next.perft(depth.saturating_sub(1)))
+            })
+            .collect()
+    }
+}
+
+impl Rules for Position {
+    fn side_to_move(&self) -> Player {
+        Position::side_to_move(self)
+    }
+
+    fn piece_at(&self, square: (u32, u32)) -> Option<Piece> {
+        self.board().get(square)
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        Position::legal_moves(self)
+    }
+
+    fn make_move(&mut self, mv: Move) -> Option<Piece> {
+        Position::make_move(self, mv)
+    }
+
+    fn in_check(&self, player: Player) -> bool {
+        Position::in_check(self, player)
+    }
+
+    fn capture_square(&self, mv: Move) -> Option<(u32, u32)> {
+        Position::capture_square(self, mv)
+    }
+
+    fn evaluate(&self) -> i32 {
+        eval::evaluate(self)
+    }
+
+    fn status(&self) -> GameStatus {
+        Position::status(self)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::piece::PieceType;
+use crate::position::{Move, Position};
+use crate::square::{parse_square, square_name};
+
+impl Position {
+    /// Writes `mv` in Standard Algebraic Notation, e.g. `Nbd7`, `exd5`,
+    /// `e8=Q+` or `O-O`. `mv` has to be legal in this position.
+    pub fn to_san(&self, mv: Move) -> String {
+        let mut san = match self.castling_rook(mv) {
+            Some(_) if mv.to.0 > mv.from.0 => "O-O".to_string(),
+            Some(_) => "O-O-O".to_string(),
+            None => self.san_without_suffix(mv),
+        };
+        let mut next = self.clone();
+        next.make_move(mv);
+        if next.in_check(next.side_to_move()) {
+            if next.legal_moves().is_empty() {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+        san
+    }
+
+    fn san_without_suffix(&self, mv: Move) -> String {
+        let piece = match self.board().get(mv.from) {
+            Some(piece) => piece,
+            None => return String::new(),
+        };
+        let capture = self.capture_square(mv).is_some();
+        let mut san = String::new();
+        if piece.piece_type == PieceType::PAWN {
+            if capture {
+                san.push_str(&square_name(mv.from)[..1]);
+            }
+        } else {
+            san.push(piece.piece_type.to_char());
+            // name the file, rank or both if another piece of the same
+            // type could go to the same square
+            let others: Vec<(u32, u32)> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| other.to == mv.to && other.from != mv.from)
+                .filter(|other| self.board().get(other.from) == Some(piece))
+                .map(|other| other.from)
+                .collect();
+            let from = square_name(mv.from);
+            if !others.is_empty() {
+                if others.iter().all(|other| other.0 != mv.from.0) {
+                    san.push_str(&from[..1]);
+                } else if others.iter().all(|other| other.1 != mv.from.1) {
+                    san.push_str(&from[1..]);
+                } else {
+                    san.push_str(&from);
+                }
+            }
+        }
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&square_name(mv.to));
+        if let Some(piece_type) = mv.promotion {
+            san.push('=');
+            san.push(piece_type.to_char());
+        }
+        san
+    }
+
+    /// Finds the legal move written in Standard Algebraic Notation.
+    /// Check marks and annotations like `!?` are ignored, as are needless
+    /// disambiguations and a missing `=` before the promotion piece.
+    pub fn parse_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(|c| "+#!?".contains(c));
+        let legal = self.legal_moves();
+
+        let castling = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(king_side) = castling {
+            return legal.into_iter().find(|&mv| {
+                self.castling_rook(mv).is_some() && (mv.to.0 > mv.from.0) == king_side
+            });
+        }
+
+        let mut chars: Vec<char> = san.chars().filter(|&c| c != 'x' && c != '=').collect();
+        let piece_type = match chars.first() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let piece_type = PieceType::from_char(*c)?;
+                chars.remove(0);
+                piece_type
+            }
+            _ => PieceType::PAWN,
+        };
+        let promotion = match chars.last() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let promotion = PieceType::from_char(*c)?;
+                chars.pop();
+                Some(promotion)
+            }
+            _ => None,
+        };
+        // the target square is the last letter with the digits after it
+        let file_at = chars.iter().rposition(|c| c.is_ascii_lowercase())?;
+        let to = parse_square(&chars[file_at..].iter().collect::<String>())?;
+        let hint: String = chars[..file_at].iter().collect();
+        let hint_file = hint.chars().find(|c| c.is_ascii_lowercase());
+        let hint_rank: Option<u32> = hint
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .ok();
+
+        let mut found = legal.into_iter().filter(|mv| {
+            let from = square_name(mv.from);
+            mv.to == to
+                && mv.promotion == promotion
+                && self.board().get(mv.from).map(|piece| piece.piece_type) == Some(piece_type)
+                && hint_file.is_none_or(|file| from.starts_with(file))
+                && hint_rank.is_none_or(|rank| mv.from.1 + 1 == rank)
+        });
+        let mv = found.next()?;
+        match found.next() {
+            Some(_) => None,
+            None => Some(mv),
+        }
+    }
+}
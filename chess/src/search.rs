@@ -0,0 +1,214 @@
+use crate::eval::value;
+use crate::position::Move;
+use crate::rules::Rules;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Score of being mated right now. Mates further away score a bit less
+/// so the quickest one is preferred.
+pub const MATE: i32 = 100_000;
+
+/// How long to search for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Deepest iteration in plies.
+    pub depth: u32,
+    /// Stop once this much time has passed, keeping the deepest finished iteration.
+    pub time: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            depth: 4,
+            time: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` if the side to move has no legal move.
+    pub best_move: Option<Move>,
+    /// Centipawns from the side to move's point of view.
+    pub score: i32,
+    /// Depth of the deepest finished iteration.
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+/// Finds the best move with iterative deepening alpha-beta search.
+/// At least one iteration is always finished, whatever the time limit.
+pub fn search<R: Rules>(position: &R, limits: &Limits) -> SearchResult {
+    search_until(position, limits, &AtomicBool::new(false))
+}
+
+/// Like `search`, but also stops as soon as `stop` is set from another thread.
+pub fn search_until<R: Rules>(position: &R, limits: &Limits, stop: &AtomicBool) -> SearchResult {
+    let mut searcher = Searcher {
+        limits: *limits,
+        stop,
+        start: Instant::now(),
+        nodes: 0,
+        depth: 0,
+        aborted: false,
+    };
+    let mut moves = ordered_moves(position);
+    let mut result = SearchResult {
+        best_move: moves.first().copied(),
+        score: 0,
+        depth: 0,
+        nodes: 0,
+    };
+    if moves.is_empty() {
+        result.score = if position.in_check(position.side_to_move()) {
+            -MATE
+        } else {
+            0
+        };
+        return result;
+    }
+
+    for depth in 1..=limits.depth.max(1) {
+        searcher.depth = depth;
+        let mut alpha = -MATE - 1;
+        let mut best = moves[0];
+        for &mv in &moves {
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -searcher.alpha_beta(&next, depth - 1, 1, -MATE - 1, -alpha);
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best = mv;
+            }
+        }
+        if searcher.aborted {
+            break;
+        }
+        result.best_move = Some(best);
+        result.score = alpha;
+        result.depth = depth;
+        // search the best move first in the next iteration
+        moves.retain(|&mv| mv != best);
+        moves.insert(0, best);
+        if alpha.abs() >= MATE - depth as i32 {
+            break;
+        }
+    }
+    result.nodes = searcher.nodes;
+    result
+}
+
+struct Searcher<'a> {
+    limits: Limits,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    /// Depth of the running iteration.
+    depth: u32,
+    aborted: bool,
+}
+
+impl Searcher<'_> {
+    fn alpha_beta<R: Rules>(
+        &mut self,
+        position: &R,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.count_node();
+        if self.aborted {
+            return 0;
+        }
+        if depth == 0 {
+            return self.quiescence(position, alpha, beta);
+        }
+        let moves = ordered_moves(position);
+        if moves.is_empty() {
+            return if position.in_check(position.side_to_move()) {
+                -MATE + ply
+            } else {
+                0
+            };
+        }
+        for mv in moves {
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -self.alpha_beta(&next, depth - 1, ply + 1, -beta, -alpha);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Keeps searching captures so the evaluation is not taken in the
+    /// middle of an exchange.
+    fn quiescence<R: Rules>(&mut self, position: &R, mut alpha: i32, beta: i32) -> i32 {
+        self.count_node();
+        let stand_pat = position.evaluate();
+        if stand_pat >= beta {
+            return beta;
+        }
+        alpha = alpha.max(stand_pat);
+        for mv in ordered_moves(position) {
+            if position.capture_square(mv).is_none() {
+                // ordered_moves puts all captures first
+                break;
+            }
+            let mut next = position.clone();
+            next.make_move(mv);
+            let score = -self.quiescence(&next, -beta, -alpha);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        // the first iteration always finishes so there is a move to play
+        if self.nodes.is_multiple_of(1024) && self.depth > 1 {
+            if let Some(time) = self.limits.time {
+                if self.start.elapsed() >= time {
+                    self.aborted = true;
+                }
+            }
+            if self.stop.load(Ordering::Relaxed) {
+                self.aborted = true;
+            }
+        }
+    }
+}
+
+/// Legal moves with captures first, most valuable victim by the least
+/// valuable attacker first.
+fn ordered_moves<R: Rules>(position: &R) -> Vec<Move> {
+    let mut moves: Vec<(i32, Move)> = position
+        .legal_moves()
+        .into_iter()
+        .map(|mv| {
+            let victim = position
+                .capture_square(mv)
+                .and_then(|square| position.piece_at(square));
+            let score = match (victim, position.piece_at(mv.from)) {
+                (Some(victim), Some(attacker)) => {
+                    10 * value(victim.piece_type) - value(attacker.piece_type) + 10_000
+                }
+                _ => mv.promotion.map_or(0, value),
+            };
+            (score, mv)
+        })
+        .collect();
+    moves.sort_by_key(|(score, _)| Reverse(*score));
+    moves.into_iter().map(|(_, mv)| mv).collect()
+}
@@ -0,0 +1,18 @@
+/// Name of a square in algebraic notation, `(4, 3)` is `"e4"`.
+pub fn square_name((x, y): (u32, u32)) -> String {
+    format!("{}{}", (b'a' + x as u8) as char, y + 1)
+}
+
+/// Reads a square in algebraic notation, the inverse of `square_name`.
+pub fn parse_square(name: &str) -> Option<(u32, u32)> {
+    let mut chars = name.chars();
+    let file = chars.next()?;
+    if !file.is_ascii_lowercase() {
+        return None;
+    }
+    let rank: u32 = chars.as_str().parse().ok()?;
+    if rank == 0 {
+        return None;
+    }
+    Some((file as u32 - 'a' as u32, rank - 1))
+}
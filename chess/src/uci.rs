@@ -0,0 +1,324 @@
+//! The Universal Chess Interface, the text protocol chess engines and
+//! GUIs talk over stdin and stdout.
+use crate::bitboard::BitPosition;
+use crate::game::Game;
+use crate::piece::PieceType;
+use crate::player::Player;
+use crate::position::{Move, Position};
+use crate::search::{search_until, Limits, SearchResult, MATE};
+use crate::square::{parse_square, square_name};
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Iterations searched by `go infinite`, enough to only end by `stop`.
+const MAX_DEPTH: u32 = 64;
+
+impl Move {
+    /// The move in UCI notation, e.g. `e2e4` or `e7e8q`.
+    pub fn to_uci(&self) -> String {
+        let mut uci = square_name(self.from) + &square_name(self.to);
+        if let Some(piece_type) = self.promotion {
+            uci.push(piece_type.to_char().to_ascii_lowercase());
+        }
+        uci
+    }
+
+    /// Reads a move in UCI notation without checking it against a position.
+    pub fn from_uci(uci: &str) -> Option<Move> {
+        let promotion = match uci.chars().last() {
+            Some(c) if c.is_ascii_alphabetic() => Some(PieceType::from_char(c)?),
+            _ => None,
+        };
+        let squares = match promotion {
+            Some(_) => &uci[..uci.len() - 1],
+            None => uci,
+        };
+        // the second square starts at the second letter
+        let split = squares
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| c.is_ascii_lowercase())?
+            .0;
+        Some(Move {
+            from: parse_square(&squares[..split])?,
+            to: parse_square(&squares[split..])?,
+            promotion,
+        })
+    }
+}
+
+impl Position {
+    /// Finds the legal move written in UCI notation. Squares off the board
+    /// give `None`, like any other illegal move.
+    pub fn parse_uci(&self, uci: &str) -> Option<Move> {
+        let on_board = |(x, y): (u32, u32)| self.board().contains(x as i32, y as i32);
+        Move::from_uci(uci).filter(|&mv| on_board(mv.from) && on_board(mv.to) && self.is_legal(mv))
+    }
+}
+
+/// The `position` command leading to the current position of `game`.
+pub fn position_command(game: &Game) -> String {
+    let mut command = if *game.start() == Position::standard() {
+        "position startpos".to_string()
+    } else {
+        format!("position fen {}", game.start().to_fen())
+    };
+    let moves = &game.moves()[..game.ply()];
+    if !moves.is_empty() {
+        command.push_str(" moves");
+        for mv in moves {
+            command.push(' ');
+            command.push_str(&mv.to_uci());
+        }
+    }
+    command
+}
+
+/// A chess engine running as a child process.
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    /// What the engine calls itself in its `id name` line.
+    pub name: Option<String>,
+}
+
+impl UciEngine {
+    /// Starts the engine and waits until it is ready to take commands.
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[String]) -> io::Result<UciEngine> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut engine = UciEngine {
+            child,
+            stdin,
+            stdout,
+            name: None,
+        };
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = Some(name.to_string());
+            }
+            if line == "uciok" {
+                break;
+            }
+        }
+        engine.send("ucinewgame")?;
+        engine.wait_ready()?;
+        Ok(engine)
+    }
+
+    pub fn send(&mut self, command: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    /// Sends `isready` and waits for the answer.
+    pub fn wait_ready(&mut self) -> io::Result<()> {
+        self.send("isready")?;
+        while self.read_line()? != "readyok" {}
+        Ok(())
+    }
+
+    /// Asks for the best move in the current position of `game`. `go` is the
+    /// full go command, e.g. `go movetime 1000`. Returns `None` if the engine
+    /// has no move to play.
+    pub fn best_move(&mut self, game: &Game, go: &str) -> io::Result<Option<Move>> {
+        self.send(&position_command(game))?;
+        self.send(go)?;
+        loop {
+            let line = self.read_line()?;
+            let mut words = line.split_whitespace();
+            if words.next() != Some("bestmove") {
+                continue;
+            }
+            return match words.next() {
+                None | Some("(none)") | Some("0000") => Ok(None),
+                Some(uci) => match game.position().parse_uci(uci) {
+                    Some(mv) => Ok(Some(mv)),
+                    None => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("engine sent illegal move {}", uci),
+                    )),
+                },
+            };
+        }
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "engine closed its output",
+            ));
+        }
+        Ok(line.trim().to_string())
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if self.send("quit").is_err() || self.child.try_wait().ok().flatten().is_none() {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// Runs our own engine on UCI commands read from `input` until `quit` or
+/// the end of the input. Searches run on their own thread so `stop` and
+/// `isready` are answered while thinking.
+pub fn serve_uci<R, W>(input: R, output: W) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write + Send + 'static,
+{
+    let output = Arc::new(Mutex::new(output));
+    let mut game = Game::new(Position::standard());
+    let mut running: Option<(JoinHandle<()>, Arc<AtomicBool>)> = None;
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            Some(command) => command,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+        match command {
+            "uci" => reply(&output, &["id name boardgame", "id author Emi", "uciok"])?,
+            "isready" => reply(&output, &["readyok"])?,
+            "ucinewgame" => {
+                stop(&mut running);
+                game = Game::new(Position::standard());
+            }
+            "position" => {
+                stop(&mut running);
+                match parse_position(&args) {
+                    Some(parsed) => game = parsed,
+                    None => reply(
+                        &output,
+                        &[&format!("info string invalid position: {}", line)],
+                    )?,
+                }
+            }
+            "go" => {
+                stop(&mut running);
+                let limits = go_limits(&args, game.position().side_to_move());
+                let stop_flag = Arc::new(AtomicBool::new(false));
+                let position = game.position().clone();
+                let (output, flag) = (Arc::clone(&output), Arc::clone(&stop_flag));
+                let handle = thread::spawn(move || {
+                    let result = match BitPosition::from_position(&position) {
+                        Some(bits) => search_until(&bits, &limits, &flag),
+                        None => search_until(&position, &limits, &flag),
+                    };
+                    // nobody is left to tell if the output is closed
+                    let _ = reply(&output, &[&info(&result), &best_move(&result)]);
+                });
+                running = Some((handle, stop_flag));
+            }
+            "stop" => stop(&mut running),
+            "quit" => break,
+            // debug, setoption, ponderhit and anything unknown
+            _ => {}
+        }
+    }
+    stop(&mut running);
+    Ok(())
+}
+
+/// Writes `lines` to the shared output at once.
+fn reply<W: Write>(output: &Mutex<W>, lines: &[&str]) -> io::Result<()> {
+    let mut output = output.lock().expect("output lock poisoned");
+    for line in lines {
+        writeln!(output, "{}", line)?;
+    }
+    output.flush()
+}
+
+/// Ends the running search, which then still sends its best move.
+fn stop(running: &mut Option<(JoinHandle<()>, Arc<AtomicBool>)>) {
+    if let Some((handle, stop)) = running.take() {
+        stop.store(true, Ordering::Relaxed);
+        let _ = handle.join();
+    }
+}
+
+/// The game set up by the arguments of a `position` command.
+fn parse_position(args: &[&str]) -> Option<Game> {
+    let moves_at = args
+        .iter()
+        .position(|&arg| arg == "moves")
+        .unwrap_or(args.len());
+    let start = match *args.first()? {
+        "startpos" => Position::standard(),
+        "fen" => Position::from_fen(&args[1..moves_at].join(" ")).ok()?,
+        _ => return None,
+    };
+    let mut game = Game::new(start);
+    for uci in args.iter().skip(moves_at + 1) {
+        let mv = game.position().parse_uci(uci)?;
+        game.play(mv);
+    }
+    Some(game)
+}
+
+/// Search limits from the arguments of a `go` command. With a clock the
+/// engine spends a thirtieth of its time, or the share until the next time
+/// control, plus the increment.
+fn go_limits(args: &[&str], side: Player) -> Limits {
+    let value = |name: &str| -> Option<u64> {
+        let at = args.iter().position(|&arg| arg == name)?;
+        args.get(at + 1)?.parse().ok()
+    };
+    let (time, increment) = match side {
+        Player::SECOND => ("btime", "binc"),
+        _ => ("wtime", "winc"),
+    };
+    let movetime = value("movetime").or_else(|| {
+        let left = value(time)?;
+        let share = left / value("movestogo").unwrap_or(30).max(1) + value(increment).unwrap_or(0);
+        // never risk the flag on a single move
+        Some(share.min(left / 2))
+    });
+    let infinite = args.contains(&"infinite");
+    Limits {
+        depth: value("depth").map_or(MAX_DEPTH, |depth| depth as u32),
+        time: match movetime {
+            Some(ms) if !infinite => Some(Duration::from_millis(ms)),
+            _ => None,
+        },
+    }
+}
+
+fn info(result: &SearchResult) -> String {
+    let score = if result.score.abs() >= MATE - MAX_DEPTH as i32 {
+        let moves = (MATE - result.score.abs() + 1) / 2;
+        format!("mate {}", if result.score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", result.score)
+    };
+    format!(
+        "info depth {} score {} nodes {}",
+        result.depth, score, result.nodes
+    )
+}
+
+fn best_move(result: &SearchResult) -> String {
+    match result.best_move {
+        Some(mv) => format!("bestmove {}", mv.to_uci()),
+        None => "bestmove 0000".to_string(),
+    }
+}
@@ -0,0 +1,45 @@
+use chess::{BitPosition, Position, Rules, START_FEN};
+
+const FENS: [&str; 5] = [
+    START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    // pawns on the far rank, which no game reaches but a FEN may hold
+    "P6k/8/8/8/8/8/8/K7 w - - 0 1",
+    "k7/8/8/8/8/8/8/p6K b - - 0 1",
+];
+
+fn sorted_moves<R: Rules>(position: &R) -> Vec<String> {
+    let mut moves: Vec<String> = position
+        .legal_moves()
+        .iter()
+        .map(|mv| mv.to_uci())
+        .collect();
+    moves.sort();
+    moves
+}
+
+#[test]
+fn only_standard_boards() {
+    let wide = Position::from_fen("4k4/9/9/9/9/9/9/4K4 w - - 0 1").unwrap();
+    assert_eq!(BitPosition::from_position(&wide), None);
+}
+
+/// Every move two plies deep leads to the same position in both representations.
+#[test]
+fn agrees_with_the_general_position() {
+    for fen in FENS.iter() {
+        let position = Position::from_fen(fen).unwrap();
+        let bits = BitPosition::from_position(&position).unwrap();
+        assert_eq!(bits.to_position(), position);
+        assert_eq!(sorted_moves(&bits), sorted_moves(&position));
+        for mv in position.legal_moves() {
+            let (mut next, mut next_bits) = (position.clone(), bits.clone());
+            assert_eq!(next_bits.make_move(mv), next.make_move(mv));
+            assert_eq!(next_bits.to_position().to_fen(), next.to_fen());
+            assert_eq!(sorted_moves(&next_bits), sorted_moves(&next));
+            assert_eq!(next_bits.evaluate(), next.evaluate());
+            assert_eq!(next_bits.status(), next.status());
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chess::{Game, GameStatus, Player, Position};
+
+fn play(uci_moves: &[&str]) -> Game {
+    let mut game = Game::new(Position::standard());
+    for uci in uci_moves {
+        let mv = game.position().parse_uci(uci).unwrap();
+        game.play(mv);
+    }
+    game
+}
+
+#[test]
+fn fools_mate() {
+    let game = play(&["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert!(game.position().in_check(Player::FIRST));
+    assert_eq!(
+        game.position().status(),
+        GameStatus::Checkmate(Player::SECOND)
+    );
+}
+
+#[test]
+fn stalemate() {
+    let position = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    assert!(!position.in_check(Player::SECOND));
+    assert_eq!(position.status(), GameStatus::Stalemate);
+}
+
+#[test]
+fn ongoing() {
+    assert_eq!(Position::standard().status(), GameStatus::Ongoing);
+    let game = play(&["e2e4", "f7f6", "d1h5"]);
+    assert!(game.position().in_check(Player::SECOND));
+    assert_eq!(game.position().status(), GameStatus::Ongoing);
+}
+
+/// The rook on e2 stands between its king and the black rook, it may
+/// only move along the e file.
+#[test]
+fn pinned_piece_stays_on_the_pin_line() {
+    let position = Position::from_fen("4k3/4r3/8/8/8/8/4R3/4K3 w - - 0 1").unwrap();
+    let moves = position.moves_from((4, 1));
+    assert_eq!(moves.len(), 5);
+    assert!(moves.iter().all(|mv| mv.to.0 == 4));
+}
+
+#[test]
+fn king_cannot_move_into_check() {
+    let position = Position::from_fen("4k3/8/8/8/8/8/r7/4K3 w - - 0 1").unwrap();
+    let mut targets: Vec<_> = position.moves_from((4, 0)).iter().map(|mv| mv.to).collect();
+    targets.sort();
+    assert_eq!(targets, [(3, 0), (5, 0)]);
+}
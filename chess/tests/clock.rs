@@ -0,0 +1,72 @@
+use chess::{Clock, Increment, Player, TimeControl};
+use std::time::Duration;
+
+fn secs(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+fn control(increment: Increment) -> TimeControl {
+    TimeControl {
+        initial: secs(60),
+        increment,
+    }
+}
+
+#[test]
+fn fischer_adds_the_increment() {
+    let mut clock = Clock::new(TimeControl::fischer(5, 3));
+    clock.tick(Player::FIRST, secs(10));
+    clock.press(Player::FIRST);
+    assert_eq!(clock.remaining(Player::FIRST), secs(293));
+    assert_eq!(clock.remaining(Player::SECOND), secs(300));
+}
+
+#[test]
+fn bronstein_gives_back_at_most_the_delay() {
+    let mut clock = Clock::new(control(Increment::Bronstein(secs(5))));
+    clock.tick(Player::FIRST, secs(2));
+    clock.press(Player::FIRST);
+    assert_eq!(clock.remaining(Player::FIRST), secs(60));
+    clock.tick(Player::SECOND, secs(8));
+    clock.press(Player::SECOND);
+    assert_eq!(clock.remaining(Player::SECOND), secs(57));
+}
+
+#[test]
+fn delay_holds_the_clock() {
+    let mut clock = Clock::new(control(Increment::Delay(secs(5))));
+    clock.tick(Player::FIRST, secs(3));
+    assert_eq!(clock.remaining(Player::FIRST), secs(60));
+    clock.tick(Player::FIRST, secs(4));
+    assert_eq!(clock.remaining(Player::FIRST), secs(58));
+    clock.press(Player::FIRST);
+    clock.tick(Player::SECOND, secs(4));
+    assert_eq!(clock.remaining(Player::SECOND), secs(60));
+}
+
+#[test]
+fn flag_falls_and_stops_the_clock() {
+    let mut clock = Clock::new(control(Increment::Fischer(secs(2))));
+    clock.tick(Player::SECOND, secs(59));
+    assert_eq!(clock.flagged(), None);
+    clock.tick(Player::SECOND, secs(2));
+    assert_eq!(clock.flagged(), Some(Player::SECOND));
+    assert_eq!(clock.remaining(Player::SECOND), secs(0));
+    clock.press(Player::SECOND);
+    clock.tick(Player::FIRST, secs(10));
+    assert_eq!(clock.remaining(Player::SECOND), secs(0));
+    assert_eq!(clock.remaining(Player::FIRST), secs(60));
+}
+
+#[test]
+fn undo_forgets_the_used_delay() {
+    let mut clock = Clock::new(control(Increment::Delay(secs(5))));
+    clock.tick(Player::FIRST, secs(3));
+    // the last move of black is taken back, black is to move again
+    clock.switch();
+    clock.tick(Player::SECOND, secs(4));
+    assert_eq!(clock.remaining(Player::SECOND), secs(60));
+    clock.tick(Player::SECOND, secs(2));
+    assert_eq!(clock.remaining(Player::SECOND), secs(59));
+    assert_eq!(clock.remaining(Player::FIRST), secs(60));
+}
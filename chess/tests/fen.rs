@@ -0,0 +1,73 @@
+use chess::{FenError, Position, START_FEN};
+
+#[test]
+fn round_trip() {
+    let fens = [
+        START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnabqkbncr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBNCR w KQkq - 0 1",
+        "rnbqk/ppppp/5/PPPPP/RNBQK b - - 12 40",
+    ];
+    for fen in fens.iter() {
+        assert_eq!(Position::from_fen(fen).unwrap().to_fen(), *fen);
+    }
+    assert_eq!(Position::from_fen(START_FEN).unwrap(), Position::standard());
+}
+
+#[test]
+fn rejects_malformed_fens() {
+    let errors = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq",
+            FenError::MissingField,
+        ),
+        (
+            "rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            FenError::UnevenRows,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBXKBNR w - - 0 1",
+            FenError::InvalidPiece('X'),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 x - - 0 1",
+            FenError::InvalidSideToMove("x".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w KX - 0 1",
+            FenError::InvalidCastling("KX".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w - z9 0 1",
+            FenError::InvalidEnPassant("z9".to_string()),
+        ),
+        (
+            "8/8/8/8/8/8/8/8 w - - x 1",
+            FenError::InvalidNumber("x".to_string()),
+        ),
+    ];
+    for (fen, error) in errors.iter() {
+        assert_eq!(Position::from_fen(fen).unwrap_err(), *error, "{}", fen);
+    }
+}
+
+#[test]
+fn rejects_oversized_boards() {
+    let fens = [
+        "99999999999/8 w - - 0 1",
+        "4000000000/8 w - - 0 1",
+        "17/17 w - - 0 1",
+        "pppppppppppppppppppp/8 w - - 0 1",
+        "8/8/8/8/8/8/8/8/8/8/8/8/8/8/8/8/8 w - - 0 1",
+    ];
+    for fen in fens.iter() {
+        assert_eq!(
+            Position::from_fen(fen).unwrap_err(),
+            FenError::TooLarge,
+            "{}",
+            fen
+        );
+    }
+    assert!(Position::from_fen("16/16 w - - 0 1").is_ok());
+}
@@ -0,0 +1,125 @@
+use chess::{read_message, write_message, Connection, Game, Message, Move, Player, Position};
+use std::io::{Cursor, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+#[test]
+fn messages_round_trip() {
+    let messages = [
+        Message::Hello {
+            version: 1,
+            side: Player::SECOND,
+            start: Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap(),
+        },
+        Message::Move(Move::new((4, 1), (4, 3))),
+        Message::Resign,
+        Message::DrawOffer,
+        Message::DrawAccept,
+        Message::Chat("good luck, have fun".to_string()),
+    ];
+    let mut buffer = vec![];
+    for message in &messages {
+        write_message(&mut buffer, message).unwrap();
+    }
+    let mut input = Cursor::new(buffer);
+    for message in &messages {
+        assert_eq!(read_message(&mut input).unwrap(), *message);
+    }
+    assert_eq!(read_message(&mut input).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn length_comes_first() {
+    let mut buffer = vec![];
+    write_message(&mut buffer, &Message::Move(Move::new((4, 1), (4, 3)))).unwrap();
+    assert_eq!(buffer, b"\0\0\0\x09move e2e4");
+}
+
+#[test]
+fn rejects_garbage() {
+    let mut unknown = 4u32.to_be_bytes().to_vec();
+    unknown.extend_from_slice(b"ping");
+    let err = read_message(&mut Cursor::new(unknown)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let huge = u32::MAX.to_be_bytes().to_vec();
+    let err = read_message(&mut Cursor::new(huge)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn off_board_moves_are_illegal() {
+    let position = Position::standard();
+    for uci in &["i1i2", "a9a8", "z9z8", "e2e9"] {
+        let mv = match Message::parse(&format!("move {}", uci)) {
+            Some(Message::Move(mv)) => mv,
+            message => panic!("{} parsed as {:?}", uci, message),
+        };
+        assert!(!position.is_legal(mv), "{}", uci);
+    }
+}
+
+#[test]
+fn plays_over_localhost() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let host = thread::spawn(move || {
+        let mut host = Connection::accept(&listener, &Position::standard(), Player::SECOND).unwrap();
+        assert_eq!(host.side, Player::FIRST);
+        let mut game = Game::new(host.start.clone());
+        let e4 = Move::new((4, 1), (4, 3));
+        host.send(&Message::Move(e4)).unwrap();
+        game.play(e4);
+        match host.receive().unwrap() {
+            Message::Move(mv) => {
+                assert!(game.position().is_legal(mv));
+                game.play(mv);
+            }
+            message => panic!("expected a move, got {}", message),
+        }
+        assert_eq!(host.receive().unwrap(), Message::Chat("gg".to_string()));
+        assert_eq!(host.receive().unwrap(), Message::Resign);
+        game.ply()
+    });
+
+    let mut guest = Connection::connect(address).unwrap();
+    assert_eq!(guest.side, Player::SECOND);
+    assert_eq!(guest.start, Position::standard());
+    let mut game = Game::new(guest.start.clone());
+    match guest.receive().unwrap() {
+        Message::Move(mv) => {
+            assert!(game.position().is_legal(mv));
+            game.play(mv);
+        }
+        message => panic!("expected a move, got {}", message),
+    }
+    let e5 = Move::new((4, 6), (4, 4));
+    guest.send(&Message::Move(e5)).unwrap();
+    game.play(e5);
+    guest.send(&Message::Chat("gg".to_string())).unwrap();
+    guest.send(&Message::Resign).unwrap();
+    assert_eq!(host.join().unwrap(), 2);
+
+    // the host hung up
+    assert!(guest.receive().is_err());
+}
+
+#[test]
+fn refuses_other_versions() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let host = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let hello = Message::Hello {
+            version: 99,
+            side: Player::SECOND,
+            start: Position::standard(),
+        };
+        write_message(&mut stream, &hello).unwrap();
+        stream.flush().unwrap();
+        stream
+    });
+    let err = Connection::connect(address).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let _: TcpStream = host.join().unwrap();
+}
@@ -0,0 +1,95 @@
+//! Perft numbers from https://www.chessprogramming.org/Perft_Results,
+//! checked on both the general and the bitboard position.
+//! The deeper counts take a while in a debug build, run them with
+//! `cargo test --release -- --ignored`.
+use chess::{BitPosition, Position, Rules, START_FEN};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+fn perft(fen: &str, depth: u32) -> u64 {
+    let position = Position::from_fen(fen).unwrap();
+    let nodes = position.perft(depth);
+    let bits = BitPosition::from_position(&position).unwrap();
+    assert_eq!(
+        bits.perft(depth),
+        nodes,
+        "bitboard perft {} of {}",
+        depth,
+        fen
+    );
+    nodes
+}
+
+#[test]
+fn start_position() {
+    assert_eq!(perft(START_FEN, 0), 1);
+    assert_eq!(perft(START_FEN, 1), 20);
+    assert_eq!(perft(START_FEN, 2), 400);
+    assert_eq!(perft(START_FEN, 3), 8_902);
+}
+
+/// Castling both ways, en passant, promotions and pins.
+#[test]
+fn kiwipete() {
+    assert_eq!(perft(KIWIPETE, 1), 48);
+    assert_eq!(perft(KIWIPETE, 2), 2_039);
+}
+
+/// En passant that would expose the own king along the rank.
+#[test]
+fn position_3() {
+    assert_eq!(perft(POSITION_3, 1), 14);
+    assert_eq!(perft(POSITION_3, 2), 191);
+    assert_eq!(perft(POSITION_3, 3), 2_812);
+    assert_eq!(perft(POSITION_3, 4), 43_238);
+}
+
+/// Promotions with capture and castling out of reach by check.
+#[test]
+fn position_4() {
+    assert_eq!(perft(POSITION_4, 1), 6);
+    assert_eq!(perft(POSITION_4, 2), 264);
+    assert_eq!(perft(POSITION_4, 3), 9_467);
+}
+
+#[test]
+fn position_5() {
+    assert_eq!(perft(POSITION_5, 1), 44);
+    assert_eq!(perft(POSITION_5, 2), 1_486);
+}
+
+#[test]
+fn position_6() {
+    assert_eq!(perft(POSITION_6, 1), 46);
+    assert_eq!(perft(POSITION_6, 2), 2_079);
+}
+
+#[test]
+fn divide_adds_up() {
+    let position = Position::from_fen(KIWIPETE).unwrap();
+    let divided = position.divide(2);
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2_039);
+}
+
+#[test]
+#[ignore]
+fn start_position_deep() {
+    assert_eq!(perft(START_FEN, 4), 197_281);
+    assert_eq!(perft(START_FEN, 5), 4_865_609);
+}
+
+#[test]
+#[ignore]
+fn tricky_positions_deep() {
+    assert_eq!(perft(KIWIPETE, 3), 97_862);
+    assert_eq!(perft(KIWIPETE, 4), 4_085_603);
+    assert_eq!(perft(POSITION_3, 5), 674_624);
+    assert_eq!(perft(POSITION_4, 4), 422_333);
+    assert_eq!(perft(POSITION_5, 3), 62_379);
+    assert_eq!(perft(POSITION_6, 3), 89_890);
+}
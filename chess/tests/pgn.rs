@@ -0,0 +1,149 @@
+use chess::{Game, Move, Pgn, PgnError, PieceSet, PieceType, Player, Position};
+
+fn position(fen: &str) -> Position {
+    Position::from_fen(fen).unwrap()
+}
+
+#[test]
+fn disambiguates_by_file_and_rank() {
+    let knights = position("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1");
+    let nbd2 = Move::new((1, 0), (3, 1));
+    assert_eq!(knights.to_san(nbd2), "Nbd2");
+    assert_eq!(knights.parse_san("Nbd2"), Some(nbd2));
+    assert_eq!(knights.parse_san("Nd2"), None);
+
+    let rooks = position("k7/8/8/8/8/4R3/8/4R1K1 w - - 0 1");
+    let r1e2 = Move::new((4, 0), (4, 1));
+    assert_eq!(rooks.to_san(r1e2), "R1e2");
+    assert_eq!(rooks.parse_san("R1e2"), Some(r1e2));
+    assert_eq!(rooks.parse_san("Re2"), None);
+}
+
+#[test]
+fn promotion() {
+    let pawn = position("8/4P3/8/8/8/8/8/k3K3 w - - 0 1");
+    let queen = Move::with_promotion((4, 6), (4, 7), PieceType::QUEEN);
+    assert_eq!(pawn.to_san(queen), "e8=Q");
+    assert_eq!(pawn.parse_san("e8=Q"), Some(queen));
+    assert_eq!(pawn.parse_san("e8Q"), Some(queen));
+    assert_eq!(pawn.parse_san("e8"), None);
+}
+
+#[test]
+fn castling_with_letters_and_zeros() {
+    let rooks = position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let short = Move::new((4, 0), (6, 0));
+    let long = Move::new((4, 0), (2, 0));
+    assert_eq!(rooks.to_san(short), "O-O");
+    assert_eq!(rooks.to_san(long), "O-O-O");
+    for (san, mv) in [
+        ("O-O", short),
+        ("0-0", short),
+        ("O-O-O", long),
+        ("0-0-0", long),
+    ]
+    .iter()
+    {
+        assert_eq!(rooks.parse_san(san), Some(*mv), "{}", san);
+    }
+}
+
+#[test]
+fn check_and_mate_suffixes() {
+    let mut game = Game::new(Position::standard());
+    for san in ["f3", "e5", "g4"].iter() {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv);
+    }
+    let mate = Move::new((3, 7), (7, 3));
+    assert_eq!(game.position().to_san(mate), "Qh4#");
+    assert_eq!(game.position().parse_san("Qh4#"), Some(mate));
+    assert_eq!(game.position().parse_san("Qh4"), Some(mate));
+
+    let check = position("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    assert_eq!(check.to_san(Move::new((0, 0), (0, 7))), "Ra8+");
+}
+
+#[test]
+fn parses_move_numbers_and_zero_castling() {
+    let text = "[Event \"test\"]\n\n1. e4 e5 2.Nf3 Nc6 3. Bc4 Bc5 4. 0-0 Nf6 5. d3 5... 0-0 *\n";
+    let pgn = Pgn::parse(text).unwrap();
+    assert_eq!(pgn.tag("Event"), Some("test"));
+    assert_eq!(pgn.game.ply(), 10);
+    let board = pgn.game.position().board();
+    assert_eq!(
+        board.get((6, 0)).map(|piece| piece.piece_type),
+        Some(PieceType::KING)
+    );
+    assert_eq!(
+        board.get((6, 7)).map(|piece| piece.player),
+        Some(Player::SECOND)
+    );
+
+    let illegal = Pgn::parse("1. e4 e5 2. Ke3 *").unwrap_err();
+    assert_eq!(illegal, PgnError::IllegalMove("Ke3".to_string()));
+}
+
+#[test]
+fn export_and_parse_round_trip() {
+    let moves = [
+        "e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5", "d4", "c6", "Nf3", "Bg4", "Be2", "e6", "O-O",
+    ];
+    let mut game = Game::new(Position::standard());
+    for san in moves.iter() {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv);
+    }
+    let mut pgn = Pgn::new(game.clone());
+    pgn.set_tag("White", "Anna \"the rook\"");
+    let text = pgn.to_string();
+    let parsed = Pgn::parse(&text).unwrap();
+    assert_eq!(parsed.game.moves(), game.moves());
+    assert_eq!(parsed.game.san_moves(), moves);
+    assert_eq!(parsed.tag("White"), Some("Anna \"the rook\""));
+    assert_eq!(parsed.tag("Rules"), None);
+    assert_eq!(parsed.to_string(), text);
+
+    let from_fen = Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 30").unwrap();
+    let mut game = Game::new(from_fen);
+    game.play(Move::new((4, 7), (3, 7)));
+    game.play(Move::new((4, 1), (4, 3)));
+    let text = Pgn::new(game.clone()).to_string();
+    assert!(text.contains("30... Kd8 31. e4"), "{}", text);
+    assert_eq!(Pgn::parse(&text).unwrap().game.moves(), game.moves());
+}
+
+#[test]
+fn leaves_out_taken_back_moves() {
+    let mut game = Game::new(Position::standard());
+    game.play(game.position().parse_san("e4").unwrap());
+    game.play(game.position().parse_san("e5").unwrap());
+    game.back();
+    assert_eq!(game.san_moves(), ["e4"]);
+    let text = Pgn::new(game.clone()).to_string();
+    assert!(text.ends_with("\n1. e4 *\n"), "{}", text);
+    let parsed = Pgn::parse(&text).unwrap();
+    assert_eq!(parsed.game.ply(), 1);
+}
+
+#[test]
+fn variant_rules_round_trip() {
+    let mut start = position("r4k3r/p9/10/10/10/10/P9/R4K3R w KQkq - 0 1");
+    start.set_promotions(PieceSet::parse("QCARBN").unwrap());
+    start.set_double_step(false);
+    start.set_castling_distance(3);
+    let mut game = Game::new(start.clone());
+    for san in ["O-O", "O-O-O"].iter() {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv);
+    }
+    let text = Pgn::new(game.clone()).to_string();
+    assert!(text.contains("[Rules \"QCARBN single 3\"]"), "{}", text);
+    let parsed = Pgn::parse(&text).unwrap();
+    assert_eq!(*parsed.game.start(), start);
+    assert_eq!(parsed.game.moves(), game.moves());
+
+    let broken = text.replace("single 3", "single 1");
+    let err = Pgn::parse(&broken).unwrap_err();
+    assert_eq!(err, PgnError::InvalidRules("QCARBN single 1".to_string()));
+}
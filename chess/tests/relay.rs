@@ -0,0 +1,105 @@
+use chess::{serve_relay, Connection, Game, GameStatus, Message, Move, Player, Position};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+fn relay() -> (String, String, JoinHandle<(Game, GameStatus)>) {
+    let players = TcpListener::bind("127.0.0.1:0").unwrap();
+    let spectators = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addresses = (
+        players.local_addr().unwrap().to_string(),
+        spectators.local_addr().unwrap().to_string(),
+    );
+    let relay = thread::spawn(move || serve_relay(&players, spectators, Position::standard()).unwrap());
+    (addresses.0, addresses.1, relay)
+}
+
+#[test]
+fn spectators_see_the_game() {
+    let (players, spectators, relay) = relay();
+    let (mut early, game) = Connection::watch(&spectators).unwrap();
+    assert_eq!(early.side, Player::NONE);
+    assert_eq!(game.ply(), 0);
+
+    let mut white = Connection::connect(&players).unwrap();
+    let mut black = Connection::connect(&players).unwrap();
+    assert_eq!((white.side, black.side), (Player::FIRST, Player::SECOND));
+
+    let e4 = Move::new((4, 1), (4, 3));
+    let e5 = Move::new((4, 6), (4, 4));
+    white.send(&Message::Move(e4)).unwrap();
+    assert_eq!(black.receive().unwrap(), Message::Move(e4));
+    black.send(&Message::Move(e5)).unwrap();
+    assert_eq!(white.receive().unwrap(), Message::Move(e5));
+
+    // joining late gets the moves so far at once
+    let (mut late, game) = Connection::watch(&spectators).unwrap();
+    assert_eq!(game.moves(), &[e4, e5][..]);
+
+    black.send(&Message::Chat("gg".to_string())).unwrap();
+    assert_eq!(white.receive().unwrap(), Message::Chat("gg".to_string()));
+    white.send(&Message::Resign).unwrap();
+    assert_eq!(black.receive().unwrap(), Message::Resign);
+
+    assert_eq!(early.receive().unwrap(), Message::Move(e4));
+    assert_eq!(early.receive().unwrap(), Message::Move(e5));
+    for spectator in [&mut early, &mut late].iter_mut() {
+        assert_eq!(spectator.receive().unwrap(), Message::Chat("Black: gg".to_string()));
+        let ended = Message::Ended(GameStatus::Resignation(Player::SECOND));
+        assert_eq!(spectator.receive().unwrap(), ended);
+    }
+    let (game, status) = relay.join().unwrap();
+    assert_eq!(game.ply(), 2);
+    assert_eq!(status, GameStatus::Resignation(Player::SECOND));
+}
+
+#[test]
+fn illegal_moves_lose() {
+    let (players, _, too_far) = relay();
+    let mut white = Connection::connect(&players).unwrap();
+    let mut black = Connection::connect(&players).unwrap();
+    white.send(&Message::Move(Move::new((4, 1), (4, 4)))).unwrap();
+    assert_eq!(black.receive().unwrap(), Message::Resign);
+    let (game, status) = too_far.join().unwrap();
+    assert_eq!(game.ply(), 0);
+    assert_eq!(status, GameStatus::Resignation(Player::SECOND));
+
+    // so does a move from off the board, without bringing the relay down
+    let (players, _, off_board) = relay();
+    let mut white = Connection::connect(&players).unwrap();
+    let mut black = Connection::connect(&players).unwrap();
+    white.send(&Message::Move(Move::new((4, 1), (4, 3)))).unwrap();
+    assert_eq!(black.receive().unwrap(), Message::Move(Move::new((4, 1), (4, 3))));
+    black.send(&Message::parse("move z9z8").unwrap()).unwrap();
+    assert_eq!(white.receive().unwrap(), Message::Resign);
+    let (game, status) = off_board.join().unwrap();
+    assert_eq!(game.ply(), 1);
+    assert_eq!(status, GameStatus::Resignation(Player::FIRST));
+}
+
+#[test]
+fn leaving_loses() {
+    let (players, spectators, relay) = relay();
+    let (mut spectator, _) = Connection::watch(&spectators).unwrap();
+    let mut white = Connection::connect(&players).unwrap();
+    let black = Connection::connect(&players).unwrap();
+    drop(black);
+    let e4 = Move::new((4, 1), (4, 3));
+    // passing the move on may be how the relay notices black is gone
+    let _ = white.send(&Message::Move(e4));
+    let ended = Message::Ended(GameStatus::Resignation(Player::FIRST));
+    loop {
+        match spectator.receive().unwrap() {
+            Message::Move(mv) => assert_eq!(mv, e4),
+            message => break assert_eq!(message, ended),
+        }
+    }
+    let (_, status) = relay.join().unwrap();
+    assert_eq!(status, GameStatus::Resignation(Player::FIRST));
+}
+
+#[test]
+fn players_cannot_watch() {
+    let (players, spectators, _) = relay();
+    assert!(Connection::connect(&spectators).is_err());
+    assert!(Connection::watch(&players).is_err());
+}
@@ -0,0 +1,26 @@
+#!/bin/sh
+# Stand-in UCI engine: answers the handshake and plays the moves given as
+# arguments one after another, then has nothing left to play.
+while read -r command rest; do
+    case "$command" in
+        uci)
+            echo "id name Stand-in"
+            echo "uciok"
+            ;;
+        isready)
+            echo "readyok"
+            ;;
+        go)
+            echo "info depth 1 score cp 0"
+            if [ $# -gt 0 ]; then
+                echo "bestmove $1"
+                shift
+            else
+                echo "bestmove (none)"
+            fi
+            ;;
+        quit)
+            exit 0
+            ;;
+    esac
+done
@@ -0,0 +1,119 @@
+use chess::{position_command, serve_uci, Game, Move, Position, UciEngine};
+use std::io::{self, ErrorKind, Write};
+use std::sync::{Arc, Mutex};
+
+fn stand_in(moves: &[&str]) -> UciEngine {
+    let mut args =
+        vec![concat!(env!("CARGO_MANIFEST_DIR"), "/tests/stand_in_engine.sh").to_string()];
+    args.extend(moves.iter().map(|mv| mv.to_string()));
+    UciEngine::spawn("sh", &args).expect("failed to start the stand-in engine")
+}
+
+#[test]
+fn uci_notation() {
+    let position = Position::from_fen("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let promotion = position.parse_uci("b7b8n").unwrap();
+    assert_eq!(promotion.to_uci(), "b7b8n");
+    assert_eq!(position.parse_uci("e1c1"), Some(Move::new((4, 0), (2, 0))));
+    assert_eq!(position.parse_uci("e1e3"), None);
+    assert_eq!(position.parse_uci("b7b8"), None);
+    assert_eq!(position.parse_uci("x"), None);
+    assert_eq!(position.parse_uci("a9a8"), None);
+    assert_eq!(position.parse_uci("i1i2"), None);
+}
+
+#[test]
+fn position_commands() {
+    let mut game = Game::new(Position::standard());
+    assert_eq!(position_command(&game), "position startpos");
+    game.play(Move::new((4, 1), (4, 3)));
+    game.play(Move::new((4, 6), (4, 4)));
+    game.back();
+    assert_eq!(position_command(&game), "position startpos moves e2e4");
+
+    let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+    let game = Game::new(Position::from_fen(fen).unwrap());
+    assert_eq!(position_command(&game), format!("position fen {}", fen));
+}
+
+#[test]
+fn plays_the_engine_moves() {
+    let mut engine = stand_in(&["e7e5", "b8c6"]);
+    assert_eq!(engine.name.as_deref(), Some("Stand-in"));
+
+    let mut game = Game::new(Position::standard());
+    game.play(Move::new((4, 1), (4, 3)));
+    let reply = engine.best_move(&game, "go movetime 100").unwrap();
+    assert_eq!(reply, Some(Move::new((4, 6), (4, 4))));
+    game.play(reply.unwrap());
+    game.play(Move::new((6, 0), (5, 2)));
+    let reply = engine.best_move(&game, "go depth 1").unwrap();
+    assert_eq!(reply, Some(Move::new((1, 7), (2, 5))));
+    assert_eq!(engine.best_move(&game, "go").unwrap(), None);
+}
+
+#[test]
+fn rejects_illegal_engine_moves() {
+    let mut engine = stand_in(&["e2e5"]);
+    let game = Game::new(Position::standard());
+    let err = engine.best_move(&game, "go").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn missing_engine() {
+    assert!(UciEngine::spawn("./no-such-engine", &[]).is_err());
+}
+
+/// Output of `serve_uci` that stays readable after being handed over.
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn serve(commands: &str) -> Vec<String> {
+    let output = Shared::default();
+    serve_uci(commands.as_bytes(), output.clone()).unwrap();
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    text.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn serves_the_handshake() {
+    let lines = serve("uci\nisready\nquit\n");
+    assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+    assert!(lines.contains(&"uciok".to_string()));
+}
+
+#[test]
+fn ignores_moves_off_the_board() {
+    let lines = serve("position startpos moves a9a8\nisready\n");
+    assert_eq!(lines.last().map(String::as_str), Some("readyok"));
+}
+
+#[test]
+fn serves_mate_in_one() {
+    let lines =
+        serve("position startpos moves e2e4 e7e5 f1c4 b8c6 d1h5 g8f6\ngo depth 2\nisready\n");
+    assert!(lines.contains(&"bestmove h5f7".to_string()), "{:?}", lines);
+    assert!(lines.iter().any(|line| line.contains("score mate 1")));
+}
+
+#[test]
+fn serves_positions_from_fen() {
+    let lines = serve("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo movetime 200\n");
+    assert!(lines.contains(&"bestmove a1a8".to_string()), "{:?}", lines);
+}
+
+#[test]
+fn stops_infinite_searches() {
+    let lines = serve("position startpos\ngo infinite\nstop\n");
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+}
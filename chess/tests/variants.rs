@@ -0,0 +1,106 @@
+//! Boards other than 8x8 and the pieces and rules of chess variants.
+use chess::{
+    read_message, write_message, BitPosition, Message, Move, PieceSet, PieceType, Player, Position,
+    Rules, PROTOCOL_VERSION,
+};
+use std::io::Cursor;
+
+const CAPABLANCA: &str = "rnabqkbncr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBNCR w KQkq - 0 1";
+const GARDNER: &str = "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1";
+
+/// Perft numbers of the Capablanca start position, as variant engines
+/// count them.
+#[test]
+fn capablanca_perft() {
+    let mut position = Position::from_fen(CAPABLANCA).unwrap();
+    position.set_castling_distance(3);
+    assert!(BitPosition::from_position(&position).is_none());
+    assert_eq!(position.perft(1), 28);
+    assert_eq!(position.perft(2), 784);
+    assert_eq!(position.perft(3), 25_228);
+}
+
+#[test]
+fn compound_pieces_give_check() {
+    let archbishop = Position::from_fen("4k3/8/5A2/8/8/8/8/4K3 b - - 0 1").unwrap();
+    assert!(archbishop.in_check(Player::SECOND));
+    let chancellor = Position::from_fen("4k3/8/8/8/8/8/8/C3K3 b - - 0 1").unwrap();
+    assert!(!chancellor.in_check(Player::SECOND));
+    let chancellor = Position::from_fen("4k3/8/8/8/8/8/8/4C1K1 b - - 0 1").unwrap();
+    assert!(chancellor.in_check(Player::SECOND));
+}
+
+#[test]
+fn promotes_to_the_pieces_of_the_variant() {
+    let mut position = Position::from_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+    position.set_promotions(PieceSet::of(&[PieceType::QUEEN, PieceType::CHANCELLOR]));
+    let promotions: Vec<_> = position
+        .moves_from((0, 6))
+        .into_iter()
+        .map(|mv| mv.promotion)
+        .collect();
+    assert_eq!(
+        promotions,
+        [Some(PieceType::QUEEN), Some(PieceType::CHANCELLOR)]
+    );
+    assert!(!position.is_legal(Move::with_promotion((0, 6), (0, 7), PieceType::ROOK)));
+    assert!(BitPosition::from_position(&position).is_none());
+}
+
+#[test]
+fn small_boards_without_double_step() {
+    let mut position = Position::from_fen(GARDNER).unwrap();
+    // the pawns could not double step here anyway, they would run into
+    // the pawns of the other side
+    assert_eq!(position.legal_moves().len(), 7);
+    position.set_double_step(false);
+    assert_eq!(position.legal_moves().len(), 7);
+
+    let mut position = Position::from_fen("k5/pppppp/6/6/PPPPPP/K5 w - - 0 1").unwrap();
+    assert_eq!(position.legal_moves().len(), 6 + 6 + 1);
+    position.set_double_step(false);
+    assert_eq!(position.legal_moves().len(), 6 + 1);
+}
+
+/// On the wider board the king castles three squares, from f1 to i1 or c1.
+#[test]
+fn capablanca_castling() {
+    let mut position = Position::from_fen("r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1").unwrap();
+    position.set_castling_distance(3);
+    let king_side = position.parse_san("O-O").unwrap();
+    assert_eq!(king_side, Move::new((5, 0), (8, 0)));
+    assert_eq!(position.castling_rook(king_side), Some(Move::new((9, 0), (7, 0))));
+    let queen_side = position.parse_san("O-O-O").unwrap();
+    assert_eq!(queen_side, Move::new((5, 0), (2, 0)));
+    assert_eq!(position.to_san(queen_side), "O-O-O");
+    let mut next = position.clone();
+    next.make_move(queen_side);
+    assert_eq!(next.to_fen(), "r4k3r/10/10/10/10/10/10/2KR5R b kq - 1 1");
+
+    // the king may not pass h1, which the rook on h8 attacks
+    let mut attacked = Position::from_fen("r4k1r1r/10/10/10/10/10/10/R4K3R w KQ - 0 1").unwrap();
+    attacked.set_castling_distance(3);
+    assert_eq!(attacked.parse_san("O-O"), None);
+    assert!(attacked.parse_san("O-O-O").is_some());
+    // nor castle when the rook has to jump over a piece to land on d1
+    let mut blocked = Position::from_fen("r4k3r/10/10/10/10/10/10/R2N1K3R w KQ - 0 1").unwrap();
+    blocked.set_castling_distance(3);
+    assert_eq!(blocked.parse_san("O-O-O"), None);
+}
+
+#[test]
+fn hello_carries_the_rules() {
+    let mut start = Position::from_fen(CAPABLANCA).unwrap();
+    start.set_promotions(PieceSet::parse("QCARBN").unwrap());
+    start.set_double_step(false);
+    start.set_castling_distance(3);
+    let hello = Message::Hello {
+        version: PROTOCOL_VERSION,
+        side: Player::SECOND,
+        start,
+    };
+    assert!(hello.to_string().contains(" QCARBN single 3 "));
+    let mut buffer = vec![];
+    write_message(&mut buffer, &hello).unwrap();
+    assert_eq!(read_message(&mut Cursor::new(buffer)).unwrap(), hello);
+}